@@ -0,0 +1,52 @@
+//! Compares the owned `FormatString` parser with the borrowing `Pieces` parser on
+//! long messages.
+//!
+//! Run with `cargo bench --bench parse`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use errors::{FormatString, Piece, Pieces};
+
+/// Runs `f` repeatedly for about half a second and returns the mean time per run.
+fn measure(mut f: impl FnMut()) -> Duration {
+    let (mut runs, start) = (0, Instant::now());
+    while start.elapsed() < Duration::from_millis(500) {
+        f();
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+fn main() {
+    let placeholder = "request {request.id} from {peer:>15} failed after {elapsed:.2?}, \
+                       {{retrying}} in {}s ({attempt}/{max_attempts:#x}); ";
+
+    for repeat in [1, 16, 256] {
+        let message = placeholder.repeat(repeat);
+
+        let owned = measure(|| {
+            black_box(FormatString::parse(black_box(&message)).unwrap());
+        });
+        let borrowed = measure(|| {
+            for piece in Pieces::new(black_box(&message)) {
+                black_box(piece.unwrap());
+            }
+        });
+        let borrowed_specs = measure(|| {
+            for piece in Pieces::new(black_box(&message)) {
+                if let Piece::Placeholder(placeholder) = piece.unwrap() {
+                    black_box(placeholder.parse_spec().unwrap());
+                }
+            }
+        });
+
+        println!(
+            "{:>6} bytes: FormatString::parse {owned:>10.2?}, Pieces {borrowed:>10.2?} \
+             ({:.1}x), Pieces with specs {borrowed_specs:>10.2?} ({:.1}x)",
+            message.len(),
+            owned.as_secs_f64() / borrowed.as_secs_f64(),
+            owned.as_secs_f64() / borrowed_specs.as_secs_f64(),
+        );
+    }
+}
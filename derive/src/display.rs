@@ -0,0 +1,94 @@
+use errors::{Interpolate, Message};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Variant};
+
+/// Expands into a complete `impl core::fmt::Display`, reading messages from `#[<attr>("...")]`
+/// and any extra arguments that follow the format string.
+pub fn expand(input: &DeriveInput, attr: &str) -> syn::Result<TokenStream> {
+    let body = match &input.data {
+        Data::Enum(data) if data.variants.is_empty() => quote! { match *self {} },
+        Data::Enum(data) => {
+            let arms = crate::collect(data.variants.iter().map(|variant| {
+                let message = message(&variant.attrs, attr, &variant.ident)?;
+                let interpolate = Interpolate::parse_message(&message, variant)?;
+                interpolate.validate()?;
+                Ok(quote! { #interpolate })
+            }))?;
+
+            quote! { match self { #(#arms)* } }
+        }
+        Data::Struct(data) => {
+            // Structs are treated as a single variant matched through `Self`.
+            let variant = Variant {
+                attrs: Vec::new(),
+                ident: input.ident.clone(),
+                fields: data.fields.clone(),
+                discriminant: None,
+            };
+
+            let message = message(&input.attrs, attr, &input.ident)?;
+            let interpolate = Interpolate::parse_message(&message, &variant)?;
+            interpolate.validate()?;
+
+            let arm = interpolate.display_arm(quote! { Self });
+            quote! { match self { #arm } }
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "unions are not supported",
+            ));
+        }
+    };
+
+    let name = &input.ident;
+    let formatter = Interpolate::formatter();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, #formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+/// Finds the `#[<name>("...", args...)]` attribute, reporting an error on `item` if it is
+/// missing.
+fn message(attrs: &[Attribute], name: &str, item: impl quote::ToTokens) -> syn::Result<Message> {
+    let attr = attrs
+        .iter()
+        .find(|attr| attr.path().is_ident(name))
+        .ok_or_else(|| {
+            syn::Error::new_spanned(item, format!("missing `#[{name}(\"...\")]` attribute"))
+        })?;
+
+    attr.parse_args()
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    #[test]
+    fn test_errors_of_all_variants_are_reported() {
+        let input: DeriveInput = parse_quote! {
+            enum E {
+                #[display("{missing}")]
+                A,
+                B,
+                #[display("{ok}")]
+                C { ok: u8 },
+                #[display("{")]
+                D,
+            }
+        };
+        let error = expand(&input, "display").unwrap_err();
+
+        assert_eq!(error.into_iter().count(), 3);
+    }
+}
@@ -0,0 +1,280 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, GenericArgument, Member, PathArguments, Type};
+
+use crate::display;
+
+/// A field of a variant (or struct) together with its error attributes.
+struct Field<'a> {
+    member: Member,
+    ty: &'a Type,
+    source: bool,
+    from: bool,
+    backtrace: bool,
+}
+
+/// The error-related fields of a single variant.
+struct Variant<'a> {
+    path: TokenStream,
+    fields: Vec<Field<'a>>,
+}
+
+impl<'a> Variant<'a> {
+    fn new(path: TokenStream, fields: &'a Fields) -> syn::Result<Self> {
+        let fields = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let has = |name| field.attrs.iter().any(|attr| attr.path().is_ident(name));
+                let member = match &field.ident {
+                    Some(ident) => Member::Named(ident.clone()),
+                    None => Member::Unnamed(index.into()),
+                };
+
+                Field {
+                    member,
+                    ty: &field.ty,
+                    source: has("source"),
+                    from: has("from"),
+                    backtrace: has("backtrace"),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for attr in ["source", "from", "backtrace"] {
+            let mut tagged = fields.iter().filter(|field| match attr {
+                "source" => field.source,
+                "from" => field.from,
+                _ => field.backtrace,
+            });
+
+            if let (Some(_), Some(duplicate)) = (tagged.next(), tagged.next()) {
+                return Err(syn::Error::new_spanned(
+                    &duplicate.member,
+                    format!("only one field can be marked `#[{attr}]`"),
+                ));
+            }
+        }
+
+        Ok(Variant { path, fields })
+    }
+
+    /// The field returned by `Error::source`: `#[source]`, `#[from]` or a field named `source`.
+    fn source(&self) -> Option<&Field<'a>> {
+        self.fields
+            .iter()
+            .find(|field| field.source || field.from)
+            .or_else(|| {
+                self.fields.iter().find(
+                    |field| matches!(&field.member, Member::Named(ident) if ident == "source"),
+                )
+            })
+    }
+
+    fn from(&self) -> Option<&Field<'a>> {
+        self.fields.iter().find(|field| field.from)
+    }
+
+    /// The field holding the variant's backtrace, preferring one tagged `#[backtrace]`.
+    fn backtrace(&self) -> Option<&Field<'a>> {
+        self.fields
+            .iter()
+            .find(|field| field.backtrace)
+            .or_else(|| self.fields.iter().find(|field| field.is_backtrace()))
+    }
+}
+
+impl Field<'_> {
+    /// Whether the field holds a backtrace: it is tagged `#[backtrace]`, or its type
+    /// is `Backtrace` or `Option<Backtrace>`.
+    fn is_backtrace(&self) -> bool {
+        self.backtrace
+            || is_backtrace_type(self.ty)
+            || option_type(self.ty).is_some_and(is_backtrace_type)
+    }
+}
+
+/// Whether `ty` is a path ending in `Backtrace`.
+fn is_backtrace_type(ty: &Type) -> bool {
+    let Type::Path(ty) = ty else {
+        return false;
+    };
+
+    ty.path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "Backtrace")
+}
+
+/// The type `T` if `ty` is a path ending in `Option<T>`.
+fn option_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(ty) = ty else {
+        return None;
+    };
+    let segment = ty
+        .path
+        .segments
+        .last()
+        .filter(|segment| segment.ident == "Option")?;
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+
+    match arguments.args.first()? {
+        GenericArgument::Type(ty) if arguments.args.len() == 1 => Some(ty),
+        _ => None,
+    }
+}
+
+/// Expands `#[derive(Error)]` into `Display`, `std::error::Error` and `From` implementations.
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let variants = match &input.data {
+        Data::Enum(data) => crate::collect(data.variants.iter().map(|variant| {
+            let ident = &variant.ident;
+            Variant::new(quote! { Self::#ident }, &variant.fields)
+        }))?,
+        Data::Struct(data) => vec![Variant::new(quote! { Self }, &data.fields)?],
+        Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "unions are not supported",
+            ));
+        }
+    };
+
+    let display = display::expand(input, "error")?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let source_arms = variants.iter().filter_map(|variant| {
+        let (path, member) = (&variant.path, &variant.source()?.member);
+        Some(quote! {
+            #path { #member: source, .. } => ::core::option::Option::Some(source.__as_dyn_error()),
+        })
+    });
+
+    let source = variants
+        .iter()
+        .any(|variant| variant.source().is_some())
+        .then(|| {
+            quote! {
+                fn source(&self) -> ::core::option::Option<&(dyn ::std::error::Error + 'static)> {
+                    trait __AsDynError {
+                        fn __as_dyn_error(&self) -> &(dyn ::std::error::Error + 'static);
+                    }
+
+                    impl<T: ::std::error::Error + 'static> __AsDynError for T {
+                        fn __as_dyn_error(&self) -> &(dyn ::std::error::Error + 'static) {
+                            self
+                        }
+                    }
+
+                    impl __AsDynError for dyn ::std::error::Error + 'static {
+                        fn __as_dyn_error(&self) -> &(dyn ::std::error::Error + 'static) {
+                            self
+                        }
+                    }
+
+                    impl __AsDynError for dyn ::std::error::Error + Send + 'static {
+                        fn __as_dyn_error(&self) -> &(dyn ::std::error::Error + 'static) {
+                            self
+                        }
+                    }
+
+                    impl __AsDynError for dyn ::std::error::Error + Send + Sync + 'static {
+                        fn __as_dyn_error(&self) -> &(dyn ::std::error::Error + 'static) {
+                            self
+                        }
+                    }
+
+                    #[allow(unreachable_patterns)]
+                    match self {
+                        #(#source_arms)*
+                        _ => ::core::option::Option::None,
+                    }
+                }
+            }
+        });
+
+    // The provider API is unstable: only emit it when a field explicitly opts in, then
+    // provide the backtrace of every variant that has one
+    let provide_arms = variants.iter().filter_map(|variant| {
+        let path = &variant.path;
+        let field = variant.backtrace()?;
+        let member = &field.member;
+
+        Some(if field.backtrace && (field.source || field.from) {
+            quote! {
+                #path { #member: backtrace, .. } => ::std::error::Error::provide(backtrace, request),
+            }
+        } else if option_type(field.ty).is_some() {
+            quote! {
+                #path { #member: ::core::option::Option::Some(backtrace), .. } => {
+                    request.provide_ref::<::std::backtrace::Backtrace>(backtrace);
+                }
+            }
+        } else {
+            quote! {
+                #path { #member: backtrace, .. } => {
+                    request.provide_ref::<::std::backtrace::Backtrace>(backtrace);
+                }
+            }
+        })
+    });
+
+    let provide = variants
+        .iter()
+        .any(|variant| variant.fields.iter().any(|field| field.backtrace))
+        .then(|| {
+            quote! {
+                fn provide<'__request>(&'__request self, request: &mut ::std::error::Request<'__request>) {
+                    #[allow(unreachable_patterns)]
+                    match self {
+                        #(#provide_arms)*
+                        _ => {}
+                    }
+                }
+            }
+        });
+
+    let from_impls = variants
+        .iter()
+        .filter_map(|variant| Some((variant, variant.from()?)))
+        .map(|(variant, from)| {
+            let (path, member, ty) = (&variant.path, &from.member, from.ty);
+            let mut backtraces = Vec::new();
+
+            for field in variant.fields.iter().filter(|field| !field.from) {
+                if !field.is_backtrace() {
+                    return Err(syn::Error::new_spanned(
+                        &from.member,
+                        "`#[from]` requires every other field to be a backtrace",
+                    ));
+                }
+                backtraces.push(&field.member);
+            }
+
+            Ok(quote! {
+                impl #impl_generics ::core::convert::From<#ty> for #name #ty_generics #where_clause {
+                    fn from(source: #ty) -> Self {
+                        #path {
+                            #member: source,
+                            #(#backtraces: ::core::convert::From::from(::std::backtrace::Backtrace::capture()),)*
+                        }
+                    }
+                }
+            })
+        });
+    let from_impls = crate::collect(from_impls)?;
+
+    Ok(quote! {
+        #display
+
+        impl #impl_generics ::std::error::Error for #name #ty_generics #where_clause {
+            #source
+            #provide
+        }
+
+        #(#from_impls)*
+    })
+}
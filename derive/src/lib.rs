@@ -0,0 +1,50 @@
+//! Derive macros built on top of [`errors::Interpolate`].
+
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
+
+mod display;
+mod error;
+
+/// Derives `core::fmt::Display` from `#[display("...")]` attributes.
+///
+/// Enums need the attribute on every variant, structs on the struct itself.
+#[proc_macro_derive(Display, attributes(display))]
+pub fn derive_display(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    display::expand(&input, "display")
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `core::fmt::Display` and `std::error::Error` from `#[error("...")]` attributes.
+///
+/// - `#[source]`, or a field named `source`, is returned from `Error::source`.
+/// - `#[from]` additionally generates a `From` impl for the field's type; any other
+///   field of the variant must be a `Backtrace`, which is captured on conversion.
+/// - `#[backtrace]` exposes the field through `Error::provide`, which requires the
+///   nightly `error_generic_member_access` feature in the deriving crate.
+#[proc_macro_derive(Error, attributes(error, source, from, backtrace))]
+pub fn derive_error(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    error::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Collects `results`, reporting the errors of all of them rather than only the first.
+fn collect<T>(results: impl IntoIterator<Item = syn::Result<T>>) -> syn::Result<Vec<T>> {
+    let mut values = Vec::new();
+    let mut errors: Option<syn::Error> = None;
+    for result in results {
+        match (result, &mut errors) {
+            (Ok(value), _) => values.push(value),
+            (Err(error), Some(errors)) => errors.combine(error),
+            (Err(error), None) => errors = Some(error),
+        }
+    }
+
+    errors.map_or(Ok(values), Err)
+}
@@ -0,0 +1,93 @@
+//! `#[backtrace]` relies on the unstable provider API, so these tests only run on
+//! nightly with `RUSTFLAGS="--cfg nightly" cargo +nightly test --test backtrace`.
+#![cfg(nightly)]
+#![feature(error_generic_member_access)]
+
+use std::backtrace::Backtrace;
+use std::error::{Error as _, request_ref};
+use std::io;
+
+use errors_derive::Error;
+
+#[derive(Debug, Error)]
+enum TaskError {
+    #[error("task timed out")]
+    Timeout {
+        #[backtrace]
+        trace: Backtrace,
+    },
+
+    #[error("task failed")]
+    Failed {
+        #[source]
+        #[backtrace]
+        cause: Inner,
+    },
+
+    #[error("i/o failure")]
+    Io(#[from] io::Error),
+
+    #[error("invalid number")]
+    Parse {
+        #[from]
+        source: std::num::ParseIntError,
+        backtrace: Backtrace,
+    },
+
+    #[error("out of range")]
+    Range {
+        #[from]
+        source: std::num::TryFromIntError,
+        backtrace: Option<Backtrace>,
+    },
+}
+
+#[derive(Debug, Error)]
+#[error("inner failure")]
+struct Inner {
+    #[backtrace]
+    trace: Backtrace,
+}
+
+#[test]
+fn test_backtrace_field() {
+    let error = TaskError::Timeout {
+        trace: Backtrace::force_capture(),
+    };
+    let trace = request_ref::<Backtrace>(&error).unwrap();
+
+    assert!(matches!(&error, TaskError::Timeout { trace: own } if std::ptr::eq(own, trace)));
+}
+
+#[test]
+fn test_source_backtrace_is_forwarded() {
+    let error = TaskError::Failed {
+        cause: Inner {
+            trace: Backtrace::force_capture(),
+        },
+    };
+    let trace = request_ref::<Backtrace>(&error).unwrap();
+
+    assert!(error.source().is_some());
+    assert!(matches!(&error, TaskError::Failed { cause } if std::ptr::eq(&cause.trace, trace)));
+}
+
+#[test]
+fn test_untagged_backtrace_fields() {
+    let error = TaskError::from("x".parse::<u8>().unwrap_err());
+    let trace = request_ref::<Backtrace>(&error).unwrap();
+    assert!(matches!(&error, TaskError::Parse { backtrace, .. } if std::ptr::eq(backtrace, trace)));
+
+    let error = TaskError::from(u8::try_from(256).unwrap_err());
+    let trace = request_ref::<Backtrace>(&error).unwrap();
+    assert!(matches!(
+        &error,
+        TaskError::Range { backtrace: Some(backtrace), .. } if std::ptr::eq(backtrace, trace)
+    ));
+}
+
+#[test]
+fn test_variants_without_backtrace() {
+    let error = TaskError::from(io::Error::other("denied"));
+    assert!(request_ref::<Backtrace>(&error).is_none());
+}
@@ -0,0 +1,291 @@
+use errors_derive::Display;
+
+#[derive(Debug, Display)]
+enum Status {
+    #[display("everything is fine")]
+    Ok,
+
+    #[display("request {id} failed with {code:#x}")]
+    Failed { id: u32, code: u16 },
+
+    #[display("braces are {{escaped}}")]
+    Escaped,
+
+    #[display("map: }} {code:}>4} {code:{<4}")]
+    Filled { code: u8 },
+}
+
+#[derive(Display)]
+#[display("{host}:{port}")]
+struct Address {
+    host: &'static str,
+    port: u16,
+}
+
+#[derive(Display)]
+#[display("{value:>5}")]
+struct Padded<T: core::fmt::Display> {
+    value: T,
+}
+
+#[derive(Display)]
+enum Table {
+    #[display("|{name:^width$}|")]
+    Cell { name: &'static str, width: usize },
+
+    #[display("{:.*}")]
+    Rounded(usize, f64),
+}
+
+#[derive(Display)]
+enum Tuple {
+    #[display("{0} failed: {1}")]
+    Io(String, std::io::Error),
+
+    #[allow(dead_code)]
+    #[display("only the second: {1}")]
+    Second(u8, &'static str),
+
+    #[display("{} then {}, first again: {0:?}")]
+    Implicit(&'static str, u8),
+}
+
+#[derive(Display)]
+enum Shadowing {
+    #[display("value {f}")]
+    Named { f: u8 },
+
+    #[display("{f}", f = .0 + 1)]
+    Tuple(u8),
+}
+
+#[derive(Display)]
+#[display("{__0}/{__1}")]
+struct Synthetic {
+    __0: u8,
+    __1: u8,
+}
+
+struct Request {
+    id: u32,
+    peer: (&'static str, u16),
+}
+
+#[derive(Display)]
+enum Projected {
+    #[display("request {request.id} from {request.peer.0}:{request.peer.1}, id {request.id:>4}")]
+    Named { request: Request },
+
+    #[display("{0.id} after {1} retries")]
+    Tuple(Request, u8),
+}
+
+#[derive(Display)]
+enum Aliased {
+    #[display("{0} then {x}", x = .0 + 1)]
+    Value(u8),
+
+    #[display("{0.id}:{id:>1$}", id = .0.id)]
+    Projected(Request, usize),
+}
+
+#[derive(Display)]
+#[display("request {self.request.id}")]
+struct Wrapper {
+    request: Request,
+}
+
+#[derive(Display)]
+enum Located {
+    #[display("{} at line {}", .path.display(), .line + 1)]
+    Named {
+        path: std::path::PathBuf,
+        line: usize,
+    },
+
+    #[display("{1}: {0:>width$}", .0.to_uppercase(), .1, width = 6)]
+    Tuple(&'static str, u8),
+
+    #[display("{:.*} {}", 2, 1.0 / 3.0, "unit")]
+    Unit,
+}
+
+#[derive(Display)]
+enum Keywords {
+    #[display("{type} {match:>größe$} {self.loop.type}")]
+    Named {
+        r#type: &'static str,
+        r#match: u8,
+        größe: usize,
+        r#loop: Inner,
+    },
+
+    #[display("{} {type}", .type.len(), r#type = .r#match)]
+    Extra { r#type: &'static str, r#match: u8 },
+}
+
+struct Inner {
+    r#type: char,
+}
+
+const MAX_RETRIES: u8 = 3;
+const WIDTH: usize = 4;
+
+#[derive(Display)]
+enum Captured {
+    #[display("gave up after {MAX_RETRIES} retries")]
+    Unit,
+
+    #[display("{0} of {MAX_RETRIES:>WIDTH$}")]
+    Tuple(u8),
+
+    #[display("{attempt:.PRECISION$} of {MAX_RETRIES}")]
+    Named { attempt: f32 },
+}
+
+const PRECISION: usize = 1;
+
+#[derive(Display)]
+#[display("unit")]
+struct Unit;
+
+#[derive(Display)]
+enum Never {}
+
+#[test]
+fn test_unit_variants() {
+    assert_eq!(Status::Ok.to_string(), "everything is fine");
+    assert_eq!(Status::Escaped.to_string(), "braces are {escaped}");
+}
+
+#[test]
+fn test_brace_fill() {
+    let status = Status::Filled { code: 7 };
+    assert_eq!(status.to_string(), "map: } }}}7 7{{{");
+}
+
+#[test]
+fn test_named_variants() {
+    let status = Status::Failed { id: 7, code: 0x1f };
+    assert_eq!(status.to_string(), "request 7 failed with 0x1f");
+}
+
+#[test]
+fn test_structs() {
+    let address = Address {
+        host: "localhost",
+        port: 8080,
+    };
+    assert_eq!(address.to_string(), "localhost:8080");
+    assert_eq!(Unit.to_string(), "unit");
+}
+
+#[test]
+fn test_generics() {
+    assert_eq!(Padded { value: 42 }.to_string(), "   42");
+    assert_eq!(Padded { value: "ab" }.to_string(), "   ab");
+}
+
+#[test]
+fn test_count_arguments() {
+    let cell = Table::Cell {
+        name: "id",
+        width: 6,
+    };
+    assert_eq!(cell.to_string(), "|  id  |");
+    assert_eq!(Table::Rounded(2, 1.23456).to_string(), "1.23");
+}
+
+#[test]
+fn test_tuple_variants() {
+    let io = Tuple::Io("read".into(), std::io::Error::other("denied"));
+    assert_eq!(io.to_string(), "read failed: denied");
+    assert_eq!(Tuple::Second(1, "b").to_string(), "only the second: b");
+    assert_eq!(
+        Tuple::Implicit("a", 2).to_string(),
+        "a then 2, first again: \"a\""
+    );
+}
+
+#[test]
+fn test_member_paths() {
+    let request = || Request {
+        id: 7,
+        peer: ("localhost", 80),
+    };
+
+    assert_eq!(
+        Projected::Named { request: request() }.to_string(),
+        "request 7 from localhost:80, id    7"
+    );
+    assert_eq!(
+        Projected::Tuple(request(), 3).to_string(),
+        "7 after 3 retries"
+    );
+    assert_eq!(Wrapper { request: request() }.to_string(), "request 7");
+}
+
+#[test]
+fn test_fields_used_by_placeholders_and_shorthands() {
+    let request = Request {
+        id: 7,
+        peer: ("localhost", 80),
+    };
+
+    assert_eq!(Aliased::Value(1).to_string(), "1 then 2");
+    assert_eq!(Aliased::Projected(request, 3).to_string(), "7:  7");
+}
+
+#[test]
+fn test_extra_arguments() {
+    let named = Located::Named {
+        path: "src/lib.rs".into(),
+        line: 9,
+    };
+    assert_eq!(named.to_string(), "src/lib.rs at line 10");
+    assert_eq!(Located::Tuple("ab", 1).to_string(), "1:     AB");
+    assert_eq!(Located::Unit.to_string(), "0.33 unit");
+}
+
+#[test]
+fn test_keyword_and_unicode_fields() {
+    let named = Keywords::Named {
+        r#type: "io",
+        r#match: 7,
+        größe: 3,
+        r#loop: Inner { r#type: 'x' },
+    };
+    assert_eq!(named.to_string(), "io   7 x");
+
+    let extra = Keywords::Extra {
+        r#type: "abc",
+        r#match: 1,
+    };
+    assert_eq!(extra.to_string(), "3 1");
+}
+
+#[test]
+fn test_captured_constants() {
+    assert_eq!(Captured::Unit.to_string(), "gave up after 3 retries");
+    assert_eq!(Captured::Tuple(1).to_string(), "1 of    3");
+
+    let named = Captured::Named { attempt: 2.0 };
+    assert_eq!(named.to_string(), "2.0 of 3");
+}
+
+#[test]
+fn test_fields_named_like_positional_arguments() {
+    assert_eq!(Synthetic { __0: 1, __1: 2 }.to_string(), "1/2");
+}
+
+#[test]
+fn test_fields_named_like_the_formatter() {
+    assert_eq!(Shadowing::Named { f: 1 }.to_string(), "value 1");
+    assert_eq!(Shadowing::Tuple(1).to_string(), "2");
+}
+
+#[test]
+fn test_empty_enum() {
+    fn assert_display<T: core::fmt::Display>() {}
+    assert_display::<Never>();
+}
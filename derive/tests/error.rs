@@ -0,0 +1,108 @@
+use std::backtrace::Backtrace;
+use std::error::Error as _;
+use std::{fmt, io};
+
+use errors_derive::Error;
+
+#[derive(Debug, Error)]
+enum ConfigError {
+    #[error("failed to read {path}")]
+    Read {
+        path: &'static str,
+        #[source]
+        cause: io::Error,
+    },
+
+    #[error("invalid number")]
+    Parse(#[from] std::num::ParseIntError),
+
+    #[error("i/o failure")]
+    Io {
+        #[from]
+        source: io::Error,
+        backtrace: Backtrace,
+    },
+
+    #[error("plugin failed")]
+    Plugin {
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[error("missing key {key}")]
+    Missing { key: String },
+
+    #[error("out of range")]
+    Range {
+        #[from]
+        source: std::num::TryFromIntError,
+        backtrace: Option<Backtrace>,
+    },
+}
+
+#[derive(Debug, Error)]
+#[error("wrapped")]
+struct Wrapped(#[from] ConfigError);
+
+#[derive(Debug, Error)]
+#[error("{0}")]
+struct Generic<E: fmt::Debug + fmt::Display>(E);
+
+#[test]
+fn test_display() {
+    let error = ConfigError::Missing { key: "port".into() };
+    assert_eq!(error.to_string(), "missing key port");
+}
+
+#[test]
+fn test_source() {
+    let error = ConfigError::Read {
+        path: "config.toml",
+        cause: io::Error::other("denied"),
+    };
+    assert_eq!(error.to_string(), "failed to read config.toml");
+    assert_eq!(error.source().unwrap().to_string(), "denied");
+
+    let error = ConfigError::Plugin {
+        source: "crashed".into(),
+    };
+    assert_eq!(error.source().unwrap().to_string(), "crashed");
+
+    let error = ConfigError::Missing { key: "port".into() };
+    assert!(error.source().is_none());
+}
+
+#[test]
+fn test_from() {
+    let error = ConfigError::from("x".parse::<u8>().unwrap_err());
+    assert!(matches!(error, ConfigError::Parse(_)));
+    assert!(error.source().is_some());
+
+    let error = ConfigError::from(io::Error::other("disk"));
+    assert_eq!(error.source().unwrap().to_string(), "disk");
+
+    // The backtrace is captured on conversion, subject to `RUST_BACKTRACE`
+    let ConfigError::Io { backtrace, .. } = &error else {
+        panic!("expected `ConfigError::Io`");
+    };
+    assert_eq!(backtrace.status(), Backtrace::capture().status());
+
+    let wrapped = Wrapped::from(error);
+    assert_eq!(wrapped.source().unwrap().to_string(), "i/o failure");
+
+    // Optional backtraces are captured too
+    let error = ConfigError::from(u8::try_from(256).unwrap_err());
+    assert!(matches!(
+        error,
+        ConfigError::Range {
+            backtrace: Some(_),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_generics() {
+    let error = Generic("boxed");
+    assert_eq!(error.to_string(), "boxed");
+    assert!(error.source().is_none());
+}
@@ -0,0 +1,77 @@
+//! The generated code must compile without warnings in user crates.
+#![deny(warnings)]
+
+use std::io;
+
+use errors_derive::{Display, Error};
+
+#[derive(Display)]
+pub enum Event {
+    #[display("started")]
+    Started,
+
+    #[display("request {id} took {elapsed:?}")]
+    Finished {
+        id: u32,
+        elapsed: std::time::Duration,
+        retries: u8,
+        tags: Vec<String>,
+    },
+
+    #[display("{value:>width$}")]
+    Padded {
+        value: u8,
+        width: usize,
+        unused: bool,
+    },
+
+    #[display("second field: {1}")]
+    Tuple(u8, u8, u8),
+
+    #[display("{} ({id})", .path.len(), id = .id + 1)]
+    Extra { path: String, id: u8, unused: bool },
+
+    #[display("{}", .1)]
+    ExtraTuple(u8, u8),
+
+    #[display("{type}")]
+    Keyword { r#type: u8, r#match: u8 },
+
+    #[display("retried {LIMIT:>WIDTH$} times")]
+    Captured { unused: u8 },
+}
+
+const LIMIT: u8 = 3;
+const WIDTH: usize = 2;
+
+#[derive(Display)]
+#[display("{name}")]
+pub struct Named {
+    pub name: String,
+    pub unused: u8,
+}
+
+#[derive(Debug, Error)]
+pub enum ReadError {
+    #[error("failed to read {path}")]
+    Read {
+        path: String,
+        #[source]
+        cause: io::Error,
+    },
+
+    #[error("i/o failure")]
+    Io(#[from] io::Error),
+}
+
+#[test]
+fn test_compiles_without_warnings() {
+    let event = Event::Finished {
+        id: 1,
+        elapsed: std::time::Duration::from_millis(5),
+        retries: 0,
+        tags: Vec::new(),
+    };
+    assert_eq!(event.to_string(), "request 1 took 5ms");
+    assert_eq!(Event::Tuple(1, 2, 3).to_string(), "second field: 2");
+}
@@ -0,0 +1,17 @@
+//! Checks the parser against the reference `format_args!` grammar on arbitrary
+//! strings.
+//!
+//! Run with `cargo +nightly fuzz run parse` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[path = "../../tests/oracle/mod.rs"]
+mod oracle;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        oracle::check(text);
+    }
+});
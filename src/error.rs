@@ -0,0 +1,190 @@
+use core::fmt;
+use core::ops::Range;
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+/// An error found while parsing a format string.
+///
+/// The fields are private, so that the `alloc` feature can add the message without
+/// changing how the error is built or matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatError {
+    kind: FormatErrorKind,
+    #[cfg(feature = "alloc")]
+    message: String,
+    byte_range: Range<usize>,
+}
+
+/// The kind of a [`FormatError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FormatErrorKind {
+    /// A `{` without a closing `}`.
+    Unterminated,
+
+    /// A `}` that does not close a placeholder.
+    UnmatchedClose,
+
+    /// An unexpected character after a placeholder's argument.
+    InvalidArgument(char),
+
+    /// A raw identifier such as `{r#type}`, which should be written `{type}`.
+    RawIdentifier,
+
+    /// `{_}`.
+    Underscore,
+
+    /// An argument index or count that does not fit into a `u16`.
+    IntegerOverflow,
+
+    /// A numeric member that is not a tuple index, e.g. `{a.01}` or `{a.4294967296}`.
+    InvalidMember,
+
+    /// An unknown type in a spec, e.g. `{:y}`.
+    UnknownFormatTrait,
+
+    /// An unexpected character after a spec's type.
+    InvalidSpec(char),
+}
+
+impl FormatError {
+    /// Creates an error about `text`, the offending text found at `byte_range`.
+    #[cfg_attr(not(feature = "alloc"), allow(unused_variables))]
+    pub(crate) fn new(kind: FormatErrorKind, text: &str, byte_range: Range<usize>) -> Self {
+        FormatError {
+            kind,
+            #[cfg(feature = "alloc")]
+            message: alloc::string::ToString::to_string(&Description {
+                kind,
+                text: Some(text),
+            }),
+            byte_range,
+        }
+    }
+
+    /// The kind of problem.
+    pub fn kind(&self) -> FormatErrorKind {
+        self.kind
+    }
+
+    /// Description of the problem.
+    #[cfg(feature = "alloc")]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Byte range of the offending text in the format string.
+    pub fn byte_range(&self) -> &Range<usize> {
+        &self.byte_range
+    }
+
+    /// Converts the error into a `syn::Error` reported at `span`, typically the
+    /// span of the string literal the format string was read from.
+    ///
+    /// The message is followed by the note of the error's kind, as rustc reports it.
+    #[cfg(feature = "syn")]
+    pub fn to_syn_error(&self, span: proc_macro2::Span) -> syn::Error {
+        match self.kind.note() {
+            Some(note) => syn::Error::new(span, format!("{}\n= note: {note}", self.message)),
+            None => syn::Error::new(span, &self.message),
+        }
+    }
+}
+
+impl FormatErrorKind {
+    /// The note rustc adds to the error, e.g. how to escape a brace.
+    pub fn note(self) -> Option<&'static str> {
+        match self {
+            FormatErrorKind::Unterminated | FormatErrorKind::InvalidArgument(_) => {
+                Some("if you intended to print `{`, you can escape it using `{{`")
+            }
+            FormatErrorKind::UnmatchedClose => {
+                Some("if you intended to print `}`, you can escape it using `}}`")
+            }
+            FormatErrorKind::RawIdentifier => Some(
+                "identifiers in format strings can be keywords and don't need to be prefixed \
+                 with `r#`",
+            ),
+            FormatErrorKind::Underscore => Some("argument name cannot be a single underscore"),
+            FormatErrorKind::IntegerOverflow
+            | FormatErrorKind::InvalidMember
+            | FormatErrorKind::UnknownFormatTrait
+            | FormatErrorKind::InvalidSpec(_) => None,
+        }
+    }
+}
+
+/// Describes an error of `kind`, including the offending text when it is known.
+struct Description<'a> {
+    kind: FormatErrorKind,
+    text: Option<&'a str>,
+}
+
+impl fmt::Display for Description<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            FormatErrorKind::Unterminated => {
+                f.write_str("invalid format string: expected `}` but string was terminated")
+            }
+            FormatErrorKind::UnmatchedClose => {
+                f.write_str("invalid format string: unmatched `}` found")
+            }
+            FormatErrorKind::InvalidArgument(c) => {
+                write!(f, "invalid format string: expected `}}`, found `{c}`")
+            }
+            FormatErrorKind::RawIdentifier => {
+                f.write_str("invalid format string: raw identifiers are not supported")
+            }
+            FormatErrorKind::Underscore => {
+                f.write_str("invalid format string: invalid argument name `_`")
+            }
+            FormatErrorKind::IntegerOverflow => match self.text {
+                Some(digits) => write!(
+                    f,
+                    "invalid format string: integer `{digits}` does not fit into the type \
+                     `u16` whose range is `0..=65535`"
+                ),
+                None => f.write_str(
+                    "invalid format string: integer does not fit into the type `u16` whose \
+                     range is `0..=65535`",
+                ),
+            },
+            FormatErrorKind::InvalidMember => match self.text {
+                Some(member) => write!(
+                    f,
+                    "invalid format string: expected a field name or tuple index after `.`, \
+                     found `{member}`"
+                ),
+                None => f.write_str(
+                    "invalid format string: expected a field name or tuple index after `.`",
+                ),
+            },
+            FormatErrorKind::UnknownFormatTrait => match self.text {
+                Some(ty) => write!(f, "unknown format trait `{ty}`"),
+                None => f.write_str("unknown format trait"),
+            },
+            FormatErrorKind::InvalidSpec(c) => {
+                write!(f, "invalid format spec: expected `}}`, found `{c}`")
+            }
+        }
+    }
+}
+
+impl fmt::Display for FormatError {
+    #[cfg(feature = "alloc")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = Description {
+            kind: self.kind,
+            text: None,
+        };
+        write!(f, "{description}")
+    }
+}
+
+impl core::error::Error for FormatError {}
@@ -0,0 +1,576 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+use crate::pieces::{Piece, Pieces};
+use crate::{Count, FormatError, FormatSpec};
+
+/// A format string split into literal text and placeholders.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FormatString {
+    /// The segments of the format string, in source order.
+    pub segments: Vec<Segment>,
+}
+
+/// A piece of a [`FormatString`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// Literal text exactly as written, escape sequences included.
+    Literal(String),
+
+    /// A `{...}` placeholder.
+    Placeholder(Placeholder),
+}
+
+/// A single `{argument:spec}` placeholder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    /// The argument the placeholder refers to.
+    pub argument: Argument,
+
+    /// Members accessed on the argument, e.g. `["inner", "code"]` for `{error.inner.code}`
+    /// or `["1"]` for `{0.1}`.
+    pub members: Vec<String>,
+
+    /// The format spec following `:`, empty if there is none.
+    pub spec: FormatSpec,
+
+    /// Byte range of the placeholder in the source, braces included.
+    pub byte_range: Range<usize>,
+}
+
+/// The positional arguments a format string refers to, see
+/// [`FormatString::positional_arguments`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PositionalArguments {
+    /// For each position up to the highest one referenced, the indices of the
+    /// placeholders referring to it, as their value or a count.
+    pub placeholders: Vec<Vec<usize>>,
+}
+
+/// The argument referenced by a placeholder.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Argument {
+    /// `{}`: the next positional argument.
+    Implicit,
+
+    /// `{0}`, `{1}`, ...: an explicit positional argument.
+    Positional(usize),
+
+    /// `{name}`: a named argument.
+    Named(String),
+}
+
+impl FormatString {
+    /// Parses the format string into literal segments and placeholders.
+    ///
+    /// Fails on unterminated placeholders, unmatched `}`, invalid argument names
+    /// and invalid format specs.
+    pub fn parse(text: impl AsRef<str>) -> Result<FormatString, FormatError> {
+        let text = text.as_ref();
+        let mut segments = Vec::new();
+
+        for piece in Pieces::new(text) {
+            match piece? {
+                // Consecutive literal pieces, split at escapes, form one segment
+                Piece::Literal { byte_range, .. } => match segments.last_mut() {
+                    Some(Segment::Literal(literal)) => literal.push_str(&text[byte_range]),
+                    _ => segments.push(Segment::Literal(text[byte_range].to_string())),
+                },
+                Piece::Placeholder(placeholder) => {
+                    segments.push(Segment::Placeholder(placeholder.into_owned()?));
+                }
+            }
+        }
+
+        Ok(FormatString { segments })
+    }
+
+    /// Iterates over the placeholders, skipping literal text.
+    pub fn placeholders(&self) -> impl Iterator<Item = &Placeholder> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Placeholder(placeholder) => Some(placeholder),
+            Segment::Literal(_) => None,
+        })
+    }
+
+    /// Resolves implicit arguments, `{}` and `.*`, to the positions `format_args!`
+    /// gives them, so that placeholders can be reordered or removed safely.
+    ///
+    /// Arguments are visited in source order, and within a placeholder in the
+    /// order width, precision, value. Each implicit argument takes the position
+    /// after the previous implicit one, starting at 0; explicit positions and
+    /// names do not affect it. So `{} {:.*}` becomes `{0} {2:.1$}`, and
+    /// `{} {1} {0} {}` becomes `{0} {1} {0} {1}`.
+    pub fn with_explicit_positions(&self) -> FormatString {
+        let mut next = 0;
+        self.map_arguments(|argument| match argument {
+            Argument::Implicit => {
+                next += 1;
+                Argument::Positional(next - 1)
+            }
+            argument => argument.clone(),
+        })
+    }
+
+    /// Reports which positional arguments the format string refers to, with
+    /// implicit arguments resolved as by [`FormatString::with_explicit_positions`].
+    ///
+    /// Member paths count as a reference to their root, so `{0.path}` uses position 0.
+    pub fn positional_arguments(&self) -> PositionalArguments {
+        let mut positions = PositionalArguments::default();
+        let format = self.with_explicit_positions();
+
+        for (index, placeholder) in format.placeholders().enumerate() {
+            for argument in placeholder.arguments() {
+                let Argument::Positional(position) = *argument else {
+                    continue;
+                };
+
+                if positions.placeholders.len() <= position {
+                    positions.placeholders.resize(position + 1, Vec::new());
+                }
+                if positions.placeholders[position].last() != Some(&index) {
+                    positions.placeholders[position].push(index);
+                }
+            }
+        }
+
+        positions
+    }
+
+    /// Replaces positional arguments, implicit ones included, with the names `name`
+    /// returns for their positions.
+    ///
+    /// With `name` mapping 0 to `path`, `{} {0:?}` becomes `{path} {path:?}`.
+    pub fn with_named_positions(&self, mut name: impl FnMut(usize) -> String) -> FormatString {
+        self.with_explicit_positions()
+            .map_arguments(|argument| match argument {
+                Argument::Positional(index) => Argument::Named(name(*index)),
+                argument => argument.clone(),
+            })
+    }
+
+    /// Replaces every argument, counts included, with `f` applied to it, in the
+    /// order `format_args!` resolves them.
+    fn map_arguments(&self, mut f: impl FnMut(&Argument) -> Argument) -> FormatString {
+        let mut format = self.clone();
+
+        for segment in &mut format.segments {
+            let Segment::Placeholder(placeholder) = segment else {
+                continue;
+            };
+
+            let spec = &mut placeholder.spec;
+            for count in [&mut spec.width, &mut spec.precision].into_iter().flatten() {
+                if let Count::Argument(argument) = count {
+                    *argument = f(argument);
+                }
+            }
+            placeholder.argument = f(&placeholder.argument);
+        }
+
+        format
+    }
+}
+
+impl PositionalArguments {
+    /// The number of positional arguments `format_args!` requires, one more than
+    /// the highest position referenced.
+    pub fn required(&self) -> usize {
+        self.placeholders.len()
+    }
+
+    /// Whether a placeholder refers to the argument at `position`.
+    pub fn is_used(&self, position: usize) -> bool {
+        self.placeholders
+            .get(position)
+            .is_some_and(|placeholders| !placeholders.is_empty())
+    }
+
+    /// The positions below `count` no placeholder refers to, which `format_args!`
+    /// rejects as unused arguments when `count` arguments are given.
+    pub fn unused(&self, count: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..count).filter(|&position| !self.is_used(position))
+    }
+}
+
+/// Renders the format string in canonical syntax, which parses back to the same
+/// segments.
+///
+/// Whitespace and leading zeros in arguments, a leading `self.` and empty specs
+/// are dropped, so `{01 :}` is rendered as `{1}`. The byte ranges of placeholders
+/// still refer to the text the format string was parsed from.
+impl fmt::Display for FormatString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.segments
+            .iter()
+            .try_for_each(|segment| write!(f, "{segment}"))
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Literal(literal) => f.write_str(literal),
+            Segment::Placeholder(placeholder) => write!(f, "{placeholder}"),
+        }
+    }
+}
+
+impl fmt::Display for Placeholder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{}", self.argument)?;
+
+        for member in &self.members {
+            write!(f, ".{member}")?;
+        }
+
+        match self.spec.is_empty() {
+            true => write!(f, "}}"),
+            false => write!(f, ":{}}}", self.spec),
+        }
+    }
+}
+
+/// Renders the argument as written in a placeholder: nothing for `{}`.
+impl fmt::Display for Argument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Argument::Implicit => Ok(()),
+            Argument::Positional(index) => write!(f, "{index}"),
+            Argument::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+impl Placeholder {
+    /// Iterates over the arguments of the placeholder in the order `format_args!`
+    /// resolves them: `width$` and `precision$` counts, then the value.
+    ///
+    /// A `.*` precision takes the next positional argument before the value does.
+    pub fn arguments(&self) -> impl Iterator<Item = &Argument> {
+        [&self.spec.width, &self.spec.precision]
+            .into_iter()
+            .flatten()
+            .filter_map(|count| match count {
+                Count::Argument(argument) => Some(argument),
+                Count::Integer(_) => None,
+            })
+            .chain([&self.argument])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Argument, FormatString, Placeholder, Segment};
+    use crate::{Count, FormatSpec};
+
+    fn parse(text: &str) -> FormatString {
+        FormatString::parse(text).unwrap()
+    }
+
+    fn placeholder(
+        argument: Argument,
+        spec: Option<&str>,
+        byte_range: std::ops::Range<usize>,
+    ) -> Segment {
+        Segment::Placeholder(Placeholder {
+            argument,
+            members: Vec::new(),
+            spec: FormatSpec::parse(spec.unwrap_or_default(), 0).unwrap(),
+            byte_range,
+        })
+    }
+
+    #[test]
+    fn test_segments() {
+        assert_eq!(
+            parse("Hello, {name}! {} {1:>4}").segments,
+            vec![
+                Segment::Literal("Hello, ".to_string()),
+                placeholder(Argument::Named("name".to_string()), None, 7..13),
+                Segment::Literal("! ".to_string()),
+                placeholder(Argument::Implicit, None, 15..17),
+                Segment::Literal(" ".to_string()),
+                placeholder(Argument::Positional(1), Some(">4"), 18..24),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escapes_stay_in_literals() {
+        assert_eq!(
+            parse("{{x}} {y}").segments,
+            vec![
+                Segment::Literal("{{x}} ".to_string()),
+                placeholder(Argument::Named("y".to_string()), None, 6..9),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_byte_ranges_are_utf8_aware() {
+        let text = "größe: {value:?}";
+        let format = parse(text);
+        let range = format.placeholders().next().unwrap().byte_range.clone();
+
+        assert_eq!(&text[range], "{value:?}");
+    }
+
+    #[test]
+    fn test_spec_errors_are_offset() {
+        let error = FormatString::parse("value: {x:>>>5}").unwrap_err();
+        assert_eq!(*error.byte_range(), 12..13);
+    }
+
+    #[test]
+    fn test_whitespace_after_argument() {
+        assert_eq!(
+            parse("{ } {a :x}").segments,
+            vec![
+                placeholder(Argument::Implicit, None, 0..3),
+                Segment::Literal(" ".to_string()),
+                placeholder(Argument::Named("a".to_string()), Some("x"), 4..10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unicode_identifiers() {
+        let arguments = |text| {
+            parse(text)
+                .placeholders()
+                .map(|placeholder| placeholder.argument.clone())
+                .collect::<Vec<_>>()
+        };
+
+        // `·` is `XID_Continue` but not alphanumeric
+        assert_eq!(
+            arguments("{größe} {a·b} {ℕ:>größe$} {type}"),
+            vec![
+                Argument::Named("größe".to_string()),
+                Argument::Named("a·b".to_string()),
+                Argument::Named("ℕ".to_string()),
+                Argument::Named("type".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse("{ℕ:>größe$}")
+                .placeholders()
+                .next()
+                .unwrap()
+                .spec
+                .width,
+            Some(Count::Argument(Argument::Named("größe".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_member_paths() {
+        let members = |text| {
+            let format = parse(text);
+            let placeholder = format.placeholders().next().unwrap().clone();
+            (placeholder.argument, placeholder.members)
+        };
+
+        assert_eq!(
+            members("{request.id:>5}"),
+            (
+                Argument::Named("request".to_string()),
+                vec!["id".to_string()]
+            )
+        );
+        assert_eq!(
+            members("{0.path.1}"),
+            (
+                Argument::Positional(0),
+                vec!["path".to_string(), "1".to_string()]
+            )
+        );
+        assert_eq!(
+            members("{self.inner.code}"),
+            (
+                Argument::Named("inner".to_string()),
+                vec!["code".to_string()]
+            )
+        );
+        assert_eq!(
+            members("{self}"),
+            (Argument::Named("self".to_string()), vec![])
+        );
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let texts = [
+            "",
+            "plain {{escaped}} text",
+            "{} {0} {name:>8.2} {:#?} {0.path.1:+#010x}",
+            "{:.*} {:1$} {value:^width$.prec$e}",
+            "{größe} {type}",
+        ];
+
+        for text in texts {
+            let format = parse(text);
+            assert_eq!(format.to_string(), text);
+            assert_eq!(parse(&format.to_string()).to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_display_normalizes() {
+        assert_eq!(parse("{01 :} {a } {self.b.c}").to_string(), "{1} {a} {b.c}");
+    }
+
+    #[test]
+    fn test_explicit_positions() {
+        assert_eq!(
+            parse("{} {:.*} {name:1$} {}")
+                .with_explicit_positions()
+                .to_string(),
+            "{0} {2:.1$} {name:1$} {3}"
+        );
+    }
+
+    #[test]
+    fn test_positional_arguments() {
+        let positions = parse("{} {1} {0} {}").positional_arguments();
+        assert_eq!(positions.placeholders, vec![vec![0, 2], vec![1, 3]]);
+        assert_eq!(positions.required(), 2);
+
+        // `.*` takes a position before the value, and gaps are unused
+        let positions = parse("{:.*} {name:>3$} {0.path}").positional_arguments();
+        assert_eq!(
+            positions.placeholders,
+            vec![vec![0, 2], vec![0], vec![], vec![1]]
+        );
+        assert_eq!(positions.required(), 4);
+        assert_eq!(positions.unused(5).collect::<Vec<_>>(), vec![2, 4]);
+
+        assert_eq!(parse("{name}").positional_arguments().required(), 0);
+    }
+
+    #[test]
+    fn test_named_positions() {
+        let names = ["path", "line", "column"];
+        assert_eq!(
+            parse("{} at {}:{2:>0$} ({0:?})")
+                .with_named_positions(|index| names[index].to_string())
+                .to_string(),
+            "{path} at {line}:{column:>path$} ({path:?})"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text| {
+            let error = FormatString::parse(text).unwrap_err();
+            (error.message().to_string(), error.byte_range().clone())
+        };
+
+        assert_eq!(
+            error("missing {name"),
+            (
+                "invalid format string: expected `}` but string was terminated".to_string(),
+                8..9
+            )
+        );
+        assert_eq!(
+            error("map: }"),
+            (
+                "invalid format string: unmatched `}` found".to_string(),
+                5..6
+            )
+        );
+        assert_eq!(
+            error("{a b}"),
+            (
+                "invalid format string: expected `}`, found `b`".to_string(),
+                3..4
+            )
+        );
+        assert_eq!(
+            error("{ a}"),
+            (
+                "invalid format string: expected `}`, found `a`".to_string(),
+                2..3
+            )
+        );
+        assert_eq!(
+            error("{a-b:?}"),
+            (
+                "invalid format string: expected `}`, found `-`".to_string(),
+                2..3
+            )
+        );
+        assert_eq!(
+            error("{request.4294967296}"),
+            (
+                "invalid format string: expected a field name or tuple index after `.`, \
+                 found `4294967296`"
+                    .to_string(),
+                9..19
+            )
+        );
+        assert_eq!(
+            error("{a.01}"),
+            (
+                "invalid format string: expected a field name or tuple index after `.`, \
+                 found `01`"
+                    .to_string(),
+                3..5
+            )
+        );
+        assert_eq!(
+            error("{r#type}"),
+            (
+                "invalid format string: raw identifiers are not supported".to_string(),
+                3..7
+            )
+        );
+        assert_eq!(
+            error("{x.r#größe}"),
+            (
+                "invalid format string: raw identifiers are not supported".to_string(),
+                5..12
+            )
+        );
+        assert_eq!(
+            error("{a.}"),
+            (
+                "invalid format string: expected `}`, found `.`".to_string(),
+                2..3
+            )
+        );
+        assert_eq!(
+            error("{.a}"),
+            (
+                "invalid format string: expected `}`, found `.`".to_string(),
+                1..2
+            )
+        );
+        assert_eq!(
+            error("{_}"),
+            (
+                "invalid format string: invalid argument name `_`".to_string(),
+                1..2
+            )
+        );
+        assert_eq!(
+            error("{65536}"),
+            (
+                "invalid format string: integer `65536` does not fit into the type `u16` whose \
+                 range is `0..=65535`"
+                    .to_string(),
+                1..6
+            )
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(parse(""), FormatString::default());
+    }
+}
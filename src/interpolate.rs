@@ -0,0 +1,718 @@
+use std::collections::BTreeSet;
+
+use crate::{Argument, Count, FormatError, FormatString, Message, Segment, span};
+
+#[cfg(feature = "codegen")]
+use proc_macro2::Ident;
+
+#[cfg(feature = "codegen")]
+use quote::{ToTokens, quote};
+
+#[cfg(feature = "codegen")]
+use crate::shorthand_ident;
+
+use proc_macro2::Span;
+use syn::ext::IdentExt;
+use syn::{Expr, LitStr, Member, Variant};
+
+/// Holds the format string with placeholders and the fields used for interpolation.
+///
+/// The default `ToTokens` implementation creates match arms for the `Display` trait.
+/// You can also use the struct's fields directly to implement custom match arms
+/// for other traits.
+pub struct Interpolate<'a> {
+    /// The variant for which the format string is being interpolated.
+    pub variant: &'a Variant,
+
+    /// The format string with placeholders processed:
+    pub rewritten_text: String,
+
+    /// Set of unique arguments used in the format string, with `{}` resolved to
+    /// its position.
+    ///
+    /// Arguments only used as the root of a member path are in `projections` instead.
+    pub identifiers: BTreeSet<Argument>,
+
+    /// Member paths such as `{request.id}` or `{0.path}`, each bound to a temporary
+    /// that is named in `rewritten_text` in place of the path.
+    pub projections: Vec<Projection>,
+
+    /// The prefix of the names positional arguments are given in `rewritten_text`.
+    ///
+    /// This is `__` unless a named argument or field looks like `__0`, in which case
+    /// underscores are added until the synthetic names cannot collide with it.
+    pub positional_prefix: String,
+
+    /// The parsed format string the text was rewritten from.
+    pub format: FormatString,
+
+    /// The span of each placeholder of `format`, in order.
+    ///
+    /// When parsed with [`Interpolate::parse_lit`], these point inside the message
+    /// literal if the compiler supports sub-spans and at the whole literal otherwise.
+    /// Diagnostics about a placeholder should be reported at its span.
+    pub spans: Vec<Span>,
+
+    /// Extra positional arguments passed after the format string, see [`Message`].
+    ///
+    /// When present, positional arguments refer to these instead of tuple fields.
+    pub args: Vec<Expr>,
+
+    /// Extra `name = value` arguments, which named arguments refer to instead of
+    /// fields of the same name.
+    pub named_args: Vec<(proc_macro2::Ident, Expr)>,
+
+    /// The fields the extra arguments refer to by `.field` shorthand.
+    pub shorthand_fields: Vec<Member>,
+}
+
+/// A member path accessed on an argument, e.g. `{request.id}` or `{0.path}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Projection {
+    /// The argument the path starts from, with `{}` resolved to its position.
+    pub root: Argument,
+
+    /// The members accessed on `root`, in order.
+    pub members: Vec<String>,
+
+    /// The name of the projected value in `rewritten_text`: the positional prefix
+    /// followed by `path` and the index of the projection.
+    pub name: String,
+
+    /// The index of the first placeholder using the path, whose span its members get.
+    pub placeholder: usize,
+}
+
+impl Interpolate<'_> {
+    /// The format string with placeholders processed:
+    /// - Named values: `{name}` remains as is
+    /// - Positional values: `{n}` becomes `__n` where n is the index
+    ///   (manually specified or auto-incremented), see `positional_prefix`
+    ///
+    /// Fails if the format string is malformed, see [`FormatString::parse`].
+    pub fn parse<'a>(
+        fmt_text: impl AsRef<str>,
+        variant: &'a Variant,
+    ) -> Result<Interpolate<'a>, FormatError> {
+        let format = FormatString::parse(fmt_text)?;
+        let spans = vec![Span::call_site(); format.placeholders().count()];
+        let positional_prefix = positional_prefix(&format, variant);
+        let Rewritten {
+            text: rewritten_text,
+            identifiers,
+            projections,
+        } = rewrite(&format, &positional_prefix);
+
+        Ok(Interpolate {
+            variant,
+            rewritten_text,
+            identifiers,
+            projections,
+            positional_prefix,
+            format,
+            spans,
+            args: Vec::new(),
+            named_args: Vec::new(),
+            shorthand_fields: Vec::new(),
+        })
+    }
+
+    /// Parses the value of `message` like [`Interpolate::parse`], reporting errors
+    /// at the offending text inside the literal.
+    pub fn parse_lit<'a>(message: &LitStr, variant: &'a Variant) -> syn::Result<Interpolate<'a>> {
+        let mut interpolate = Interpolate::parse(message.value(), variant).map_err(|error| {
+            error.to_syn_error(span::subspan(message, error.byte_range().clone()))
+        })?;
+
+        interpolate.spans = interpolate
+            .format
+            .placeholders()
+            .map(|placeholder| span::subspan(message, placeholder.byte_range.clone()))
+            .collect();
+
+        Ok(interpolate)
+    }
+
+    /// Parses a message attribute's format string like [`Interpolate::parse_lit`],
+    /// along with its extra arguments.
+    pub fn parse_message<'a>(
+        message: &Message,
+        variant: &'a Variant,
+    ) -> syn::Result<Interpolate<'a>> {
+        let mut interpolate = Interpolate::parse_lit(&message.literal, variant)?;
+        interpolate.args = message.args.clone();
+        interpolate.named_args = message.named_args.clone();
+        interpolate.shorthand_fields = message.fields.clone();
+
+        Ok(interpolate)
+    }
+
+    /// The extra argument `argument` refers to: the positional argument at its index
+    /// or the named argument of the same name.
+    pub(crate) fn extra_arg(&self, argument: &Argument) -> Option<&Expr> {
+        match argument {
+            Argument::Positional(index) => self.args.get(*index),
+            Argument::Named(name) => self
+                .named_args
+                .iter()
+                .find(|(ident, _)| ident.unraw() == name)
+                .map(|(_, expr)| expr),
+            Argument::Implicit => None,
+        }
+    }
+
+    /// Whether `argument` is captured from the scope of the generated code, like
+    /// `format!("{MAX_RETRIES}")` captures a constant.
+    ///
+    /// Names in `SCREAMING_SNAKE_CASE` that are neither a field of the variant nor
+    /// an extra argument are captured; other values in scope can be passed as named
+    /// arguments, e.g. `limit = self::limit()`.
+    pub fn captures(&self, argument: &Argument) -> bool {
+        let Argument::Named(name) = argument else {
+            return false;
+        };
+
+        let constant =
+            name.chars().any(char::is_uppercase) && !name.chars().any(char::is_lowercase);
+        let field = self
+            .variant
+            .fields
+            .iter()
+            .flat_map(|field| &field.ident)
+            .any(|ident| ident.unraw() == name);
+
+        constant && !field && self.extra_arg(argument).is_none()
+    }
+}
+
+/// Finds a prefix for positional and projection names that no named argument or field
+/// could be confused with.
+fn positional_prefix(format: &FormatString, variant: &Variant) -> String {
+    let arguments = format
+        .placeholders()
+        .flat_map(|placeholder| placeholder.arguments());
+    let names = arguments
+        .filter_map(|argument| match argument {
+            Argument::Named(name) => Some(name.clone()),
+            _ => None,
+        })
+        .chain(
+            variant
+                .fields
+                .iter()
+                .flat_map(|field| &field.ident)
+                .map(|ident| ident.unraw().to_string()),
+        )
+        .collect::<Vec<_>>();
+
+    let mut prefix = "__".to_string();
+    while names.iter().any(|name| {
+        name.strip_prefix(&prefix)
+            .map(|rest| rest.strip_prefix("path").unwrap_or(rest))
+            .is_some_and(|rest| !rest.is_empty() && rest.bytes().all(|b| b.is_ascii_digit()))
+    }) {
+        prefix.push('_');
+    }
+
+    prefix
+}
+
+/// The result of [`rewrite`], see the fields of [`Interpolate`].
+struct Rewritten {
+    text: String,
+    identifiers: BTreeSet<Argument>,
+    projections: Vec<Projection>,
+}
+
+/// Rewrites the parsed format string for `write!`, collecting the arguments it uses
+/// and the member paths it accesses.
+fn rewrite(format: &FormatString, prefix: &str) -> Rewritten {
+    let (mut identifers, mut text) = (BTreeSet::new(), String::new());
+    let mut projections = Vec::<Projection>::new();
+    let mut placeholder_index = 0;
+
+    for segment in &format.with_explicit_positions().segments {
+        let placeholder = match segment {
+            Segment::Literal(literal) => {
+                text.push_str(literal);
+                continue;
+            }
+            Segment::Placeholder(placeholder) => placeholder,
+        };
+        placeholder_index += 1;
+
+        // Count arguments are rewritten like values
+        let mut spec = placeholder.spec.clone();
+        for count in [&mut spec.width, &mut spec.precision].into_iter().flatten() {
+            if let Count::Argument(argument) = count {
+                let argument = argument.clone();
+                *count = Count::Argument(Argument::Named(argument_name(&argument, prefix)));
+                identifers.insert(argument);
+            }
+        }
+
+        let argument = placeholder.argument.clone();
+        let identifier = if placeholder.members.is_empty() {
+            let identifier = argument_name(&argument, prefix);
+            identifers.insert(argument);
+            identifier
+        } else {
+            // Repeated paths share one temporary
+            let existing = projections.iter().find(|projection| {
+                projection.root == argument && projection.members == placeholder.members
+            });
+
+            match existing {
+                Some(projection) => projection.name.clone(),
+                None => {
+                    let projection = Projection {
+                        root: argument,
+                        members: placeholder.members.clone(),
+                        name: format!("{prefix}path{}", projections.len()),
+                        placeholder: placeholder_index - 1,
+                    };
+                    projections.push(projection);
+                    projections[projections.len() - 1].name.clone()
+                }
+            }
+        };
+
+        match spec {
+            spec if spec.is_empty() => text.push_str(&format!("{{{identifier}}}")),
+            spec => text.push_str(&format!("{{{identifier}:{spec}}}")),
+        }
+    }
+
+    Rewritten {
+        text,
+        identifiers: identifers,
+        projections,
+    }
+}
+
+/// Creates the identifier for a field or argument name, as a raw identifier if the
+/// name is a keyword, so that `{type}` refers to `r#type`.
+pub(crate) fn name_ident(name: &str, span: Span) -> proc_macro2::Ident {
+    match syn::parse_str::<proc_macro2::Ident>(name) {
+        Ok(_) => proc_macro2::Ident::new(name, span),
+        // These keywords cannot be raw identifiers, and `self` is valid as an argument
+        Err(_) if matches!(name, "self" | "Self" | "super" | "crate") => {
+            proc_macro2::Ident::new(name, span)
+        }
+        Err(_) => proc_macro2::Ident::new_raw(name, span),
+    }
+}
+
+/// The name of a resolved argument in the rewritten text.
+fn argument_name(argument: &Argument, prefix: &str) -> String {
+    match argument {
+        Argument::Positional(index) => format!("{prefix}{index}"),
+        Argument::Named(name) => name.clone(),
+        Argument::Implicit => unreachable!("implicit arguments are resolved before naming"),
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl Interpolate<'_> {
+    /// Builds the `Display` match arm for the variant, matching it through `path`.
+    ///
+    /// The `ToTokens` implementation uses `Self::Variant` as the path; structs can
+    /// pass `Self` to match on the struct itself. The arm writes to the formatter
+    /// named by [`Interpolate::formatter`].
+    pub fn display_arm(&self, path: impl quote::ToTokens) -> proc_macro2::TokenStream {
+        let interpolated_text = &self.rewritten_text;
+        let f = Self::formatter();
+
+        let arguments = self.identifiers.iter().filter_map(|argument| {
+            let ident = self.ident(argument)?;
+            if let Some(arg) = self.extra_arg(argument) {
+                return Some(quote! { #ident = #arg });
+            }
+
+            // Captures are passed explicitly, so that an unknown name is reported at
+            // the placeholder
+            if let (Argument::Named(name), true) = (argument, self.captures(argument)) {
+                let placeholder = self
+                    .format
+                    .placeholders()
+                    .position(|placeholder| placeholder.arguments().any(|used| used == argument));
+                let span = placeholder
+                    .map_or_else(proc_macro2::Span::call_site, |index| self.spans[index]);
+                let value = name_ident(name, span);
+                return Some(quote! { #ident = #value });
+            }
+
+            // Positional bindings are hygienic, so the format string cannot capture them
+            let binding = self.binding(argument)?;
+            match argument {
+                Argument::Positional(_) => Some(quote! { #ident = #binding }),
+                _ => None,
+            }
+        });
+        let projections = self.projections.iter().filter_map(|projection| {
+            let ident = Ident::new(&projection.name, proc_macro2::Span::mixed_site());
+            let root = match self.extra_arg(&projection.root) {
+                Some(arg) => quote! { (#arg) },
+                None => self.binding(&projection.root)?.into_token_stream(),
+            };
+
+            // Members get the placeholder's span, so errors about them point at the path
+            let span = self.spans[projection.placeholder];
+            let members = projection
+                .members
+                .iter()
+                .map(|member| match member.parse() {
+                    Ok(index) => syn::Member::Unnamed(syn::Index { index, span }),
+                    Err(_) => syn::Member::Named(name_ident(member, span)),
+                });
+
+            Some(quote! { #ident = &#root #(.#members)* })
+        });
+        let arguments = arguments.chain(projections);
+
+        match &self.variant.fields {
+            syn::Fields::Unit => {
+                quote! {
+                    #path => write!(#f, #interpolated_text #(, #arguments)*),
+                }
+            }
+            syn::Fields::Unnamed(fields) => {
+                let bindings =
+                    (0..fields.unnamed.len()).map(|index| self.build_positional_binding(index));
+
+                quote! {
+                    #path(#(#bindings),*) => write!(#f, #interpolated_text #(, #arguments)*),
+                }
+            }
+            syn::Fields::Named(fields) => {
+                // Only bind the fields used in the format string to avoid unused variables
+                let fields_ident =
+                    fields
+                        .named
+                        .iter()
+                        .flat_map(|field| &field.ident)
+                        .filter(|ident| {
+                            self.binds(&Argument::Named(ident.unraw().to_string()))
+                                || self
+                                    .shorthand_fields
+                                    .contains(&Member::Named(ident.unraw()))
+                        });
+
+                quote! {
+                    #path { #(#fields_ident,)* .. } => write!(#f, #interpolated_text #(, #arguments)*),
+                }
+            }
+        }
+    }
+
+    /// The name of the `fmt` parameter the arms of [`Interpolate::display_arm`] write to.
+    ///
+    /// It is a `Span::mixed_site` identifier, so a field named `f` cannot shadow it.
+    pub fn formatter() -> Ident {
+        Ident::new("__formatter", proc_macro2::Span::mixed_site())
+    }
+
+    /// The identifier an argument is bound to in the generated code, or `None` for
+    /// [`Argument::Implicit`], which is only named once its position is resolved, see
+    /// [`FormatString::with_explicit_positions`].
+    ///
+    /// Positional arguments get `Span::mixed_site` identifiers so they cannot clash
+    /// with anything at the call site.
+    pub fn ident(&self, argument: &Argument) -> Option<Ident> {
+        match argument {
+            Argument::Positional(index) => Some(Ident::new(
+                &format!("{}{index}", self.positional_prefix),
+                proc_macro2::Span::mixed_site(),
+            )),
+            Argument::Named(name) => Some(name_ident(name, proc_macro2::Span::call_site())),
+            Argument::Implicit => None,
+        }
+    }
+
+    /// The variable the field an argument refers to is bound to in the match arm.
+    ///
+    /// Tuple fields are bound once, to the identifier their `.N` shorthand is replaced
+    /// with, so placeholders and extra arguments can both use them.
+    fn binding(&self, argument: &Argument) -> Option<Ident> {
+        match argument {
+            Argument::Positional(index) => Some(shorthand_ident(&Member::Unnamed((*index).into()))),
+            argument => self.ident(argument),
+        }
+    }
+
+    /// Whether the field an argument refers to is used, directly or through a member path.
+    fn binds(&self, argument: &Argument) -> bool {
+        match argument {
+            Argument::Positional(_) if !self.args.is_empty() => return false,
+            argument if self.extra_arg(argument).is_some() => return false,
+            _ => {}
+        }
+
+        self.identifiers.contains(argument)
+            || self
+                .projections
+                .iter()
+                .any(|projection| projection.root == *argument)
+    }
+
+    /// Build the pattern for the tuple field at `index`, see [`Interpolate::binding`].
+    fn build_positional_binding(&self, index: usize) -> proc_macro2::TokenStream {
+        let argument = Argument::Positional(index);
+        let member = Member::Unnamed(index.into());
+
+        // If the field is not used by the message, then we don't need to bind it
+        if !self.binds(&argument) && !self.shorthand_fields.contains(&member) {
+            return quote! { _ };
+        }
+
+        self.binding(&argument).into_token_stream()
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl quote::ToTokens for Interpolate<'_> {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let variant_name = &self.variant.ident;
+        tokens.extend(self.display_arm(quote! { Self::#variant_name }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Argument, FormatString, Interpolate, Projection, argument_name, rewrite};
+    use std::collections::BTreeSet;
+
+    fn names(arguments: &BTreeSet<Argument>) -> BTreeSet<String> {
+        arguments
+            .iter()
+            .map(|argument| argument_name(argument, "__"))
+            .collect()
+    }
+
+    fn parse_internal(text: &str) -> (String, BTreeSet<String>) {
+        let rewritten = rewrite(&FormatString::parse(text).unwrap(), "__");
+        (rewritten.text, names(&rewritten.identifiers))
+    }
+
+    fn to_set<T: ToString>(values: &[T]) -> BTreeSet<String> {
+        values.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_named_placeholders() {
+        // Single named placeholder
+        assert_eq!(
+            parse_internal("Hello, {name}!"),
+            ("Hello, {name}!".to_string(), to_set(&["name"]))
+        );
+
+        // Multiple named placeholders
+        assert_eq!(
+            parse_internal("Hello, {name}! You are {age} years old."),
+            (
+                "Hello, {name}! You are {age} years old.".to_string(),
+                to_set(&["name", "age"])
+            )
+        );
+    }
+
+    #[test]
+    fn test_positional_placeholders() {
+        // Explicit positional placeholders
+        assert_eq!(
+            parse_internal("Hello, {0}! {1}"),
+            ("Hello, {__0}! {__1}".to_string(), to_set(&["__0", "__1"]))
+        );
+
+        // Implicit positional placeholders
+        assert_eq!(
+            parse_internal("Hello, {}! {}"),
+            ("Hello, {__0}! {__1}".to_string(), to_set(&["__0", "__1"]))
+        );
+
+        // Mixed explicit and implicit positional placeholders
+        // Note: The current implementation reuses indices for the same position
+        assert_eq!(
+            parse_internal("{} {1} {0} {}"),
+            (
+                "{__0} {__1} {__0} {__1}".to_string(),
+                to_set(&["__0", "__1"])
+            )
+        );
+    }
+
+    #[test]
+    fn test_mixed_named_and_positional() {
+        assert_eq!(
+            parse_internal("Hello, {}! My name is {name}. I'm {} years old."),
+            (
+                "Hello, {__0}! My name is {name}. I'm {__1} years old.".to_string(),
+                to_set(&["__0", "name", "__1"])
+            )
+        );
+    }
+
+    #[test]
+    fn test_format_specifiers() {
+        // Debug format specifier
+        assert_eq!(
+            parse_internal("Debug: {value:?}"),
+            ("Debug: {value:?}".to_string(), to_set(&["value"]))
+        );
+
+        // Hex format specifier
+        assert_eq!(
+            parse_internal("Hex: {value:x}"),
+            ("Hex: {value:x}".to_string(), to_set(&["value"]))
+        );
+
+        // Multiple format specifiers
+        assert_eq!(
+            parse_internal("Number: {num:04x} {num:#x}"),
+            (
+                "Number: {num:04x} {num:#x}".to_string(),
+                to_set(&["num", "num"])
+            )
+        );
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        // Empty string
+        assert_eq!(parse_internal(""), ("".to_string(), BTreeSet::new()));
+
+        // No placeholders
+        assert_eq!(
+            parse_internal("Just a regular string"),
+            ("Just a regular string".to_string(), BTreeSet::new())
+        );
+
+        // Only placeholders
+        assert_eq!(
+            parse_internal("{}{name}{0}"),
+            (
+                "{__0}{name}{__0}".to_string(),
+                to_set(&["__0", "name", "__0"])
+            )
+        );
+
+        // Escaped braces
+        assert_eq!(
+            parse_internal("{{escaped}} {{braces}} {name}"),
+            (
+                "{{escaped}} {{braces}} {name}".to_string(),
+                to_set(&["name"])
+            )
+        );
+    }
+
+    #[test]
+    fn test_complex_combinations() {
+        assert_eq!(
+            parse_internal("User {name}: {age} years, {height:.2}m, ID: {:08x}"),
+            (
+                "User {name}: {age} years, {height:.2}m, ID: {__0:08x}".to_string(),
+                to_set(&["name", "age", "height", "__0"])
+            )
+        );
+    }
+
+    #[test]
+    fn test_count_arguments() {
+        let rewritten = rewrite(
+            &FormatString::parse("{:.*} {value:>width$} {:1$.prec$}").unwrap(),
+            "__",
+        );
+
+        // `.*` takes the first positional argument, the value takes the second
+        assert_eq!(
+            rewritten.text,
+            "{__1:.__0$} {value:>width$} {__2:__1$.prec$}"
+        );
+        assert_eq!(
+            names(&rewritten.identifiers),
+            to_set(&["__0", "__1", "__2", "value", "width", "prec"])
+        );
+    }
+
+    #[test]
+    fn test_member_paths() {
+        let rewritten = rewrite(
+            &FormatString::parse("{request.id} {0.path:?} {request.id:>5} {self.code}").unwrap(),
+            "__",
+        );
+
+        assert_eq!(rewritten.text, "{__path0} {__path1:?} {__path0:>5} {code}");
+        assert_eq!(names(&rewritten.identifiers), to_set(&["code"]));
+        assert_eq!(
+            rewritten.projections,
+            vec![
+                Projection {
+                    root: Argument::Named("request".to_string()),
+                    members: vec!["id".to_string()],
+                    name: "__path0".to_string(),
+                    placeholder: 0,
+                },
+                Projection {
+                    root: Argument::Positional(0),
+                    members: vec!["path".to_string()],
+                    name: "__path1".to_string(),
+                    placeholder: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_positional_prefix_avoids_collisions() {
+        let variant = syn::parse_quote!(V { __1: u8 });
+        let interpolate = Interpolate::parse("{__1} {} {0:__0$}", &variant).unwrap();
+
+        assert_eq!(interpolate.positional_prefix, "___");
+        assert_eq!(interpolate.rewritten_text, "{__1} {___0} {___0:__0$}");
+        assert_eq!(
+            interpolate.identifiers,
+            BTreeSet::from([
+                Argument::Positional(0),
+                Argument::Named("__0".to_string()),
+                Argument::Named("__1".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "codegen")]
+    fn test_idents() {
+        let variant = syn::parse_quote!(V { r#type: u8 });
+        let interpolate = Interpolate::parse("{} {type}", &variant).unwrap();
+        let ident = |argument| interpolate.ident(&argument).map(|ident| ident.to_string());
+
+        assert_eq!(ident(Argument::Positional(0)), Some("__0".to_string()));
+        assert_eq!(
+            ident(Argument::Named("type".to_string())),
+            Some("r#type".to_string())
+        );
+        assert_eq!(ident(Argument::Implicit), None);
+    }
+
+    #[test]
+    fn test_closing_braces() {
+        // `}}` stays escaped for `write!`, like `{{`
+        assert_eq!(
+            parse_internal("map: }} {{{name}}}"),
+            ("map: }} {{{name}}}".to_string(), to_set(&["name"]))
+        );
+
+        let variant = syn::parse_quote!(V);
+        let literal = syn::parse_str::<syn::LitStr>(r#""map: }""#).unwrap();
+        let error = Interpolate::parse_lit(&literal, &variant).err().unwrap();
+
+        assert_eq!(
+            error.to_string(),
+            "invalid format string: unmatched `}` found\n\
+             = note: if you intended to print `}`, you can escape it using `}}`"
+        );
+        assert_eq!(error.span().start().column, 6);
+        assert_eq!(error.span().end().column, 7);
+    }
+}
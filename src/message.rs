@@ -0,0 +1,268 @@
+use proc_macro2::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::{Expr, LitStr, Member, Token};
+
+use crate::interpolate::name_ident;
+use crate::pieces::is_tuple_index;
+
+/// The contents of a message attribute: the format string followed by optional
+/// extra arguments, e.g. `"{} at line {}", .path.display(), .line`.
+///
+/// Like `format_args!`, positional arguments come first and may be followed by
+/// `name = value` arguments. Within the arguments, `.field` (or `.0` on tuple
+/// variants) is shorthand for the variant's field. When positional arguments are
+/// given, positional placeholders refer to them instead of to tuple fields; named
+/// arguments take precedence over fields of the same name.
+pub struct Message {
+    /// The format string.
+    pub literal: LitStr,
+
+    /// The extra positional arguments, with field shorthands replaced by the fields'
+    /// bindings, see [`shorthand_ident`].
+    pub args: Vec<Expr>,
+
+    /// The extra `name = value` arguments, with field shorthands replaced like `args`.
+    pub named_args: Vec<(Ident, Expr)>,
+
+    /// The fields referred to by shorthand, in order of appearance, without `r#`.
+    pub fields: Vec<Member>,
+}
+
+impl Parse for Message {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let literal = input.parse()?;
+        if input.is_empty() {
+            return Ok(Message {
+                literal,
+                args: Vec::new(),
+                named_args: Vec::new(),
+                fields: Vec::new(),
+            });
+        }
+
+        input.parse::<Token![,]>()?;
+        let mut fields = Vec::new();
+        let tokens = replace_shorthands(input.parse()?, &mut fields)?;
+        let exprs = Punctuated::<Expr, Token![,]>::parse_terminated.parse2(tokens)?;
+
+        let (mut args, mut named_args) = (Vec::new(), Vec::<(Ident, Expr)>::new());
+        for expr in exprs {
+            let name = match &expr {
+                Expr::Assign(assign) => match &*assign.left {
+                    Expr::Path(path) if path.qself.is_none() => path.path.get_ident().cloned(),
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            match (name, expr) {
+                (Some(name), Expr::Assign(assign)) => {
+                    if named_args.iter().any(|(existing, _)| *existing == name) {
+                        return Err(syn::Error::new(
+                            name.span(),
+                            format!("duplicate argument named `{name}`"),
+                        ));
+                    }
+                    named_args.push((name, *assign.right));
+                }
+                (_, expr) if !named_args.is_empty() => {
+                    return Err(syn::Error::new_spanned(
+                        expr,
+                        "positional arguments cannot follow named arguments",
+                    ));
+                }
+                (_, expr) => args.push(expr),
+            }
+        }
+
+        Ok(Message {
+            literal,
+            args,
+            named_args,
+            fields,
+        })
+    }
+}
+
+/// The identifier a field referred to by shorthand is bound to.
+///
+/// Named fields keep their name (raw if it is a keyword, so `.type` is `r#type`);
+/// tuple fields get `Span::mixed_site` identifiers, so they cannot clash with
+/// anything at the call site.
+pub fn shorthand_ident(member: &Member) -> Ident {
+    match member {
+        Member::Named(ident) => name_ident(&ident.unraw().to_string(), ident.span()),
+        Member::Unnamed(index) => {
+            Ident::new(&format!("__field{}", index.index), Span::mixed_site())
+        }
+    }
+}
+
+/// Replaces every `.field` that starts an expression with the field's binding,
+/// recording the fields in `fields`.
+///
+/// A `.` starts an expression when it is the first token of a group or follows an
+/// operator; after anything else it is a field access or method call.
+fn replace_shorthands(tokens: TokenStream, fields: &mut Vec<Member>) -> syn::Result<TokenStream> {
+    let mut output = TokenStream::new();
+    let mut tokens = tokens.into_iter().peekable();
+    let mut starts_expression = true;
+
+    while let Some(token) = tokens.next() {
+        let shorthand = match &token {
+            TokenTree::Punct(punct) if punct.as_char() == '.' && starts_expression => {
+                match tokens.peek() {
+                    Some(TokenTree::Ident(_) | TokenTree::Literal(_)) => tokens.next(),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        starts_expression = match &token {
+            TokenTree::Punct(punct) => !matches!(punct.as_char(), '.' | '?'),
+            _ => false,
+        };
+
+        let token = match (token, shorthand) {
+            (_, Some(TokenTree::Ident(ident))) => {
+                let member = Member::Named(ident.unraw());
+                output.extend([TokenTree::Ident(shorthand_ident(&member))]);
+                fields.push(member);
+                continue;
+            }
+            (_, Some(TokenTree::Literal(literal))) => {
+                // `.0.1` is lexed as `.` followed by the float `0.1`
+                let text = literal.to_string();
+                let mut indices = text.split('.');
+                let index = parse_index(indices.next().unwrap_or_default(), literal.span())?;
+
+                let member = Member::Unnamed(index);
+                output.extend([TokenTree::Ident(shorthand_ident(&member))]);
+                fields.push(member);
+
+                for index in indices {
+                    let index = parse_index(index, literal.span())?;
+                    let mut literal = Literal::u32_unsuffixed(index.index);
+                    literal.set_span(index.span);
+                    output.extend([
+                        TokenTree::Punct(Punct::new('.', Spacing::Alone)),
+                        TokenTree::Literal(literal),
+                    ]);
+                }
+                continue;
+            }
+            (TokenTree::Group(group), _) => {
+                let stream = replace_shorthands(group.stream(), fields)?;
+                let mut replaced = Group::new(group.delimiter(), stream);
+                replaced.set_span(group.span());
+
+                // Closing delimiters end an expression, except for a bare `None` group
+                starts_expression = group.delimiter() == Delimiter::None;
+                TokenTree::Group(replaced)
+            }
+            (token, _) => token,
+        };
+
+        output.extend([token]);
+    }
+
+    Ok(output)
+}
+
+/// Parses a tuple index of a field shorthand.
+fn parse_index(text: &str, span: Span) -> syn::Result<syn::Index> {
+    match text.parse() {
+        Ok(index) if is_tuple_index(text) => Ok(syn::Index { index, span }),
+        _ => Err(syn::Error::new(
+            span,
+            format!("expected a field name or tuple index after `.`, found `{text}`"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::Member;
+
+    use super::Message;
+
+    fn parse(tokens: proc_macro2::TokenStream) -> (Vec<String>, Vec<String>) {
+        let message = syn::parse2::<Message>(tokens).unwrap();
+        assert!(message.named_args.is_empty());
+        let args = message.args.iter().map(|arg| quote!(#arg).to_string());
+        let fields = message.fields.iter().map(|member| match member {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        });
+
+        (args.collect(), fields.collect())
+    }
+
+    #[test]
+    fn test_literal_only() {
+        assert_eq!(parse(quote!("{code}")), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_shorthands() {
+        assert_eq!(
+            parse(quote!("{} at {}", .path.display(), .line + 1,)),
+            (
+                vec!["path . display ()".to_string(), "line + 1".to_string()],
+                vec!["path".to_string(), "line".to_string()]
+            )
+        );
+        assert_eq!(
+            parse(quote!("{}", (.0.1, &.2, f(.name)?.len()))),
+            (
+                vec!["(__field0 . 1 , & __field2 , f (name) ? . len ())".to_string()],
+                vec!["0".to_string(), "2".to_string(), "name".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn test_named_arguments() {
+        let message = syn::parse2::<Message>(quote!("{}{x}", .0, x = .1 + 1)).unwrap();
+        let (name, value) = &message.named_args[0];
+
+        assert_eq!(message.args.len(), 1);
+        assert_eq!(name.to_string(), "x");
+        assert_eq!(quote!(#value).to_string(), "__field1 + 1");
+    }
+
+    #[test]
+    fn test_non_shorthand_dots() {
+        assert_eq!(
+            parse(quote!("{}", self.path.len())),
+            (vec!["self . path . len ()".to_string()], vec![])
+        );
+        assert_eq!(
+            parse(quote!("{:?}", 0..10)),
+            (vec!["0 .. 10".to_string()], vec![])
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |tokens| syn::parse2::<Message>(tokens).err().map(|e| e.to_string());
+
+        assert_eq!(error(quote!("{}".path)), Some("expected `,`".to_string()));
+        assert_eq!(
+            error(quote!("{}", x = 1, 2)),
+            Some("positional arguments cannot follow named arguments".to_string())
+        );
+        assert_eq!(
+            error(quote!("{x}", x = 1, x = 2)),
+            Some("duplicate argument named `x`".to_string())
+        );
+        assert_eq!(
+            error(quote!("{}", .01)),
+            Some("expected a field name or tuple index after `.`, found `01`".to_string())
+        );
+    }
+}
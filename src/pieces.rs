@@ -0,0 +1,446 @@
+use core::ops::Range;
+
+#[cfg(feature = "alloc")]
+use alloc::string::ToString;
+
+use crate::spec::RawSpec;
+use crate::{FormatError, FormatErrorKind};
+
+#[cfg(feature = "alloc")]
+use crate::{Argument, Placeholder};
+
+/// An iterator over the pieces of a format string that borrows from it and does
+/// not allocate, except for the message of an error with the `alloc` feature.
+///
+/// After an error the iterator ends.
+#[cfg_attr(
+    feature = "alloc",
+    doc = "",
+    doc = "[`FormatString::parse`](crate::FormatString::parse) collects these pieces into \
+           owned segments."
+)]
+#[derive(Debug, Clone)]
+pub struct Pieces<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+/// A piece of a format string, see [`Pieces`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Piece<'a> {
+    /// Literal text with escapes resolved, so `{{` is the piece `{`.
+    Literal {
+        text: &'a str,
+
+        /// Byte range of the text in the source, escapes included.
+        byte_range: Range<usize>,
+    },
+
+    /// A `{...}` placeholder.
+    Placeholder(RawPlaceholder<'a>),
+}
+
+/// A placeholder whose parts are slices of the format string.
+///
+/// The argument is validated while iterating; the spec is only split off and
+/// parsed by [`RawPlaceholder::parse_spec`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPlaceholder<'a> {
+    /// The argument the placeholder refers to.
+    pub argument: RawArgument<'a>,
+
+    /// The members accessed on the argument, e.g. `inner.code` for
+    /// `{error.inner.code}`, or an empty string.
+    pub member_path: &'a str,
+
+    /// The text of the format spec following `:`, empty if there is none.
+    pub spec: &'a str,
+
+    /// Byte offset of `spec` in the format string.
+    pub spec_offset: usize,
+
+    /// Byte range of the placeholder in the source, braces included.
+    pub byte_range: Range<usize>,
+}
+
+/// The argument referenced by a [`RawPlaceholder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawArgument<'a> {
+    /// `{}`: the next positional argument.
+    Implicit,
+
+    /// `{0}`, `{1}`, ...: an explicit positional argument.
+    Positional(usize),
+
+    /// `{name}`: a named argument.
+    Named(&'a str),
+}
+
+impl<'a> Pieces<'a> {
+    /// Starts iterating over the pieces of `text`.
+    pub fn new(text: &'a str) -> Pieces<'a> {
+        Pieces { text, position: 0 }
+    }
+
+    /// Parses the placeholder starting with the `{` at byte `start`.
+    ///
+    /// The argument ends at the first `:` or `}`, and the spec at the next `}`
+    /// unless that `}` is a fill character, as in `{:}>5}`.
+    fn placeholder(&self, start: usize) -> Result<RawPlaceholder<'a>, FormatError> {
+        let unterminated =
+            || FormatError::new(FormatErrorKind::Unterminated, "{", start..start + 1);
+
+        let argument_end = self.text[start + 1..]
+            .find([':', '}'])
+            .map(|length| start + 1 + length)
+            .ok_or_else(unterminated)?;
+
+        let (spec_offset, end) = if self.text[argument_end..].starts_with(':') {
+            let spec_offset = argument_end + 1;
+            let mut chars = self.text[spec_offset..].chars();
+            let fill = match (chars.next(), chars.next()) {
+                (Some(fill), Some('<' | '^' | '>')) => fill.len_utf8(),
+                _ => 0,
+            };
+
+            let length = self.text[spec_offset + fill..]
+                .find('}')
+                .ok_or_else(unterminated)?;
+            (spec_offset, spec_offset + fill + length)
+        } else {
+            (argument_end, argument_end)
+        };
+
+        let (argument, member_path) =
+            RawArgument::parse(&self.text[start + 1..argument_end], start + 1)?;
+        Ok(RawPlaceholder {
+            argument,
+            member_path,
+            spec: &self.text[spec_offset..end],
+            spec_offset,
+            byte_range: start..end + 1,
+        })
+    }
+}
+
+impl<'a> Iterator for Pieces<'a> {
+    type Item = Result<Piece<'a>, FormatError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.position;
+        let rest = &self.text[start..];
+
+        let literal = |end: usize, byte_range: Range<usize>| {
+            Some(Ok(Piece::Literal {
+                text: &rest[..end],
+                byte_range,
+            }))
+        };
+
+        match rest.find(['{', '}']) {
+            _ if rest.is_empty() => None,
+            None => {
+                self.position = self.text.len();
+                literal(rest.len(), start..self.text.len())
+            }
+            Some(0) if rest.starts_with("{{") || rest.starts_with("}}") => {
+                // A doubled brace is an escaped brace
+                self.position += 2;
+                literal(1, start..start + 2)
+            }
+            Some(0) if rest.starts_with('}') => {
+                self.position = self.text.len();
+                Some(Err(FormatError::new(
+                    FormatErrorKind::UnmatchedClose,
+                    "}",
+                    start..start + 1,
+                )))
+            }
+            Some(0) => {
+                let placeholder = self.placeholder(start);
+                self.position = match &placeholder {
+                    Ok(placeholder) => placeholder.byte_range.end,
+                    Err(_) => self.text.len(),
+                };
+                Some(placeholder.map(Piece::Placeholder))
+            }
+            Some(brace) => {
+                self.position += brace;
+                literal(brace, start..start + brace)
+            }
+        }
+    }
+}
+
+impl<'a> RawPlaceholder<'a> {
+    /// Iterates over the members accessed on the argument.
+    pub fn members(&self) -> impl Iterator<Item = &'a str> + use<'a> {
+        self.member_path
+            .split('.')
+            .filter(|member| !member.is_empty())
+    }
+
+    /// Parses the format spec without allocating.
+    pub fn parse_spec(&self) -> Result<RawSpec<'a>, FormatError> {
+        RawSpec::parse(self.spec, self.spec_offset)
+    }
+
+    /// Parses the spec and converts the placeholder into an owned [`Placeholder`].
+    #[cfg(feature = "alloc")]
+    pub fn into_owned(self) -> Result<Placeholder, FormatError> {
+        Ok(Placeholder {
+            spec: self.parse_spec()?.into_owned(),
+            argument: self.argument.into_owned(),
+            members: self.members().map(str::to_string).collect(),
+            byte_range: self.byte_range,
+        })
+    }
+}
+
+impl<'a> RawArgument<'a> {
+    /// Parses the argument of a placeholder, which starts at byte `offset` of the format
+    /// string, returning it with the path of members accessed on it.
+    ///
+    /// ```text
+    /// argument := [(integer | identifier) ('.' (integer | identifier))*] whitespace*
+    /// ```
+    ///
+    /// A leading `self.` is accepted and dropped, so `{self.code}` is `{code}`.
+    fn parse(text: &'a str, offset: usize) -> Result<(RawArgument<'a>, &'a str), FormatError> {
+        let (mut root, mut path) = (0..0, 0..0);
+        let mut position = 0;
+
+        loop {
+            let length = token_length(&text[position..]);
+
+            // Keywords can be used as is, so `{type}` refers to `r#type`
+            let raw = &text[position + length..];
+            if &text[position..position + length] == "r"
+                && raw.starts_with('#')
+                && token_length(&raw[1..]) > 0
+            {
+                let (start, length) = (offset + position + 2, token_length(&raw[1..]));
+                return Err(FormatError::new(
+                    FormatErrorKind::RawIdentifier,
+                    &raw[1..1 + length],
+                    start..start + length,
+                ));
+            }
+
+            // Numeric members must be tuple indices, like `.0` in Rust
+            let token = &text[position..position + length];
+            if position > 0
+                && token.starts_with(|c: char| c.is_ascii_digit())
+                && !is_tuple_index(token)
+            {
+                let start = offset + position;
+                return Err(FormatError::new(
+                    FormatErrorKind::InvalidMember,
+                    token,
+                    start..start + length,
+                ));
+            }
+
+            match position {
+                0 => root = 0..length,
+                _ if path.is_empty() => path = position..position + length,
+                _ => path.end = position + length,
+            }
+            position += length;
+
+            // A `.` followed by another token continues a member path
+            let rest = &text[position..];
+            if length > 0 && rest.starts_with('.') && token_length(&rest[1..]) > 0 {
+                position += 1;
+                continue;
+            }
+            break;
+        }
+
+        let rest = &text[position..];
+        let trimmed = rest.trim_start();
+        if let Some(c) = trimmed.chars().next() {
+            let position = offset + position + rest.len() - trimmed.len();
+            return Err(FormatError::new(
+                FormatErrorKind::InvalidArgument(c),
+                &trimmed[..c.len_utf8()],
+                position..position + c.len_utf8(),
+            ));
+        }
+
+        if &text[root.clone()] == "self" && !path.is_empty() {
+            let end = text[path.clone()]
+                .find('.')
+                .map_or(path.end, |dot| path.start + dot);
+            root = path.start..end;
+            path = (end + 1).min(path.end)..path.end;
+        }
+
+        let byte_range = offset + root.start..offset + root.end;
+        let argument = match &text[root] {
+            "" => RawArgument::Implicit,
+            "_" => {
+                return Err(FormatError::new(
+                    FormatErrorKind::Underscore,
+                    "_",
+                    byte_range,
+                ));
+            }
+            root if root.starts_with(|c: char| c.is_ascii_digit()) => {
+                RawArgument::Positional(parse_integer(root, byte_range.start)?)
+            }
+            root => RawArgument::Named(root),
+        };
+
+        Ok((argument, &text[path]))
+    }
+
+    /// Converts the argument into an owned [`Argument`].
+    #[cfg(feature = "alloc")]
+    pub fn into_owned(self) -> Argument {
+        match self {
+            RawArgument::Implicit => Argument::Implicit,
+            RawArgument::Positional(index) => Argument::Positional(index),
+            RawArgument::Named(name) => Argument::Named(name.to_string()),
+        }
+    }
+}
+
+/// The length of the integer or identifier at the start of `text`.
+pub(crate) fn token_length(text: &str) -> usize {
+    match text.chars().next() {
+        Some(c) if c.is_ascii_digit() => text
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len()),
+        Some(c) if is_identifier_start(c) => text
+            .find(|c: char| !is_identifier_continue(c))
+            .unwrap_or(text.len()),
+        _ => 0,
+    }
+}
+
+/// Whether `c` can start an identifier, following Rust's lexer (`_` or `XID_Start`).
+pub(crate) fn is_identifier_start(c: char) -> bool {
+    c == '_' || unicode_ident::is_xid_start(c)
+}
+
+/// Whether `c` can continue an identifier (`XID_Continue`).
+pub(crate) fn is_identifier_continue(c: char) -> bool {
+    unicode_ident::is_xid_continue(c)
+}
+
+/// Whether `digits` is a tuple index as Rust writes it: a `u32` without leading zeros.
+pub(crate) fn is_tuple_index(digits: &str) -> bool {
+    digits.parse::<u32>().is_ok() && (digits == "0" || !digits.starts_with('0'))
+}
+
+/// Parses the digits of an argument index or count starting at byte `offset`.
+///
+/// Like `format_args!`, leading zeros are allowed and values must fit into a `u16`.
+pub(crate) fn parse_integer(digits: &str, offset: usize) -> Result<usize, FormatError> {
+    digits.parse::<u16>().map(usize::from).map_err(|_| {
+        FormatError::new(
+            FormatErrorKind::IntegerOverflow,
+            digits,
+            offset..offset + digits.len(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Piece, Pieces, RawArgument};
+
+    fn pieces(text: &str) -> Vec<Piece<'_>> {
+        Pieces::new(text).collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn test_borrowed_slices() {
+        let text = "a {{b}} {name.inner:>5} {}";
+        let pieces = pieces(text);
+
+        let literals = pieces
+            .iter()
+            .filter_map(|piece| match piece {
+                Piece::Literal { text, byte_range } => Some((*text, byte_range.clone())),
+                Piece::Placeholder(_) => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            literals,
+            vec![
+                ("a ", 0..2),
+                ("{", 2..4),
+                ("b", 4..5),
+                ("}", 5..7),
+                (" ", 7..8),
+                (" ", 23..24)
+            ]
+        );
+
+        let Piece::Placeholder(placeholder) = &pieces[5] else {
+            panic!("expected a placeholder, found {:?}", pieces[5]);
+        };
+        assert_eq!(placeholder.argument, RawArgument::Named("name"));
+        assert_eq!(placeholder.members().collect::<Vec<_>>(), vec!["inner"]);
+        assert_eq!(placeholder.spec, ">5");
+        assert_eq!(&text[placeholder.spec_offset..][..2], ">5");
+        assert_eq!(placeholder.byte_range, 8..23);
+
+        // The slices point into the source
+        let range = text.as_bytes().as_ptr_range();
+        assert!(range.contains(&placeholder.spec.as_ptr()));
+    }
+
+    #[test]
+    fn test_self_prefix() {
+        let Piece::Placeholder(placeholder) = &pieces("{self.a.b.c}")[0] else {
+            panic!("expected a placeholder");
+        };
+        assert_eq!(placeholder.argument, RawArgument::Named("a"));
+        assert_eq!(placeholder.member_path, "b.c");
+    }
+
+    #[test]
+    fn test_ends_after_error() {
+        let mut pieces = Pieces::new("a } {b}");
+        assert!(matches!(pieces.next(), Some(Ok(Piece::Literal { .. }))));
+        assert!(matches!(pieces.next(), Some(Err(_))));
+        assert_eq!(pieces.next(), None);
+    }
+
+    #[test]
+    fn test_spec_is_parsed_on_demand() {
+        let Piece::Placeholder(placeholder) = &pieces("{:>>>5}")[0] else {
+            panic!("expected a placeholder");
+        };
+        let error = placeholder.parse_spec().unwrap_err();
+        assert_eq!(*error.byte_range(), 4..5);
+    }
+
+    #[test]
+    fn test_brace_fill() {
+        let specs = pieces("{:}>5}|{a:{^3}|{::<2}")
+            .into_iter()
+            .filter_map(|piece| match piece {
+                Piece::Placeholder(placeholder) => Some(placeholder.spec),
+                Piece::Literal { .. } => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(specs, vec!["}>5", "{^3", ":<2"]);
+
+        // Without an alignment, braces are not fill characters
+        let rejected = |text| {
+            Pieces::new(text).any(|piece| match piece {
+                Ok(Piece::Placeholder(placeholder)) => placeholder.parse_spec().is_err(),
+                Ok(Piece::Literal { .. }) => false,
+                Err(_) => true,
+            })
+        };
+        assert!(rejected("{:}5}"));
+        assert!(rejected("{a:{b}}"));
+        assert!(rejected("{a:b:c}"));
+        assert!(rejected("{:}>5"));
+    }
+}
@@ -0,0 +1,519 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::ops::Range;
+
+#[cfg(feature = "std")]
+use std::borrow::Borrow;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash};
+
+use crate::{Align, Count, FormatString, FormatTrait, Segment, Sign};
+
+/// A value a format string can be rendered with at runtime, see [`FormatString::render`].
+#[derive(Clone, Copy)]
+pub enum Value<'a> {
+    /// A string slice.
+    Str(&'a str),
+
+    /// A character.
+    Char(char),
+
+    /// A boolean.
+    Bool(bool),
+
+    /// A signed integer, with the number of bits of its type, e.g. 32 for an `i32`.
+    ///
+    /// Like `format!`, the binary, octal and hexadecimal format traits print negative
+    /// values in two's complement of that many bits. Widths above 64 are treated as 64.
+    Int { value: i64, bits: u32 },
+
+    /// An unsigned integer.
+    UInt(u64),
+
+    /// A floating-point number.
+    Float(f64),
+
+    /// Any other value, which only supports the `Display` format trait and is
+    /// left-aligned by default.
+    Display(&'a dyn fmt::Display),
+}
+
+/// An error found while rendering a format string at runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderError {
+    /// No value was found for the argument `name`.
+    Missing {
+        name: String,
+        byte_range: Range<usize>,
+    },
+
+    /// The value of the `width$` or `precision$` count `name` is not a `usize`.
+    InvalidCount {
+        name: String,
+        byte_range: Range<usize>,
+    },
+
+    /// The value of `name` cannot be formatted with the placeholder's format trait.
+    Unsupported {
+        name: String,
+        format_trait: FormatTrait,
+        byte_range: Range<usize>,
+    },
+}
+
+impl RenderError {
+    /// Byte range of the offending placeholder in the format string.
+    pub fn byte_range(&self) -> &Range<usize> {
+        match self {
+            RenderError::Missing { byte_range, .. }
+            | RenderError::InvalidCount { byte_range, .. }
+            | RenderError::Unsupported { byte_range, .. } => byte_range,
+        }
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::Missing { name, .. } => write!(f, "no value for argument `{name}`"),
+            RenderError::InvalidCount { name, .. } => {
+                write!(
+                    f,
+                    "argument `{name}` is used as a count, but is not a `usize`"
+                )
+            }
+            RenderError::Unsupported {
+                name, format_trait, ..
+            } => write!(
+                f,
+                "argument `{name}` cannot be formatted with `{{:{}}}`",
+                format_trait.as_str()
+            ),
+        }
+    }
+}
+
+impl core::error::Error for RenderError {}
+
+impl FormatString {
+    /// Renders the format string at runtime, looking up the value of each argument
+    /// with `lookup`.
+    ///
+    /// Named arguments are looked up by name, member paths by their dotted path
+    /// (`{request.id}` as `request.id`) and positional arguments, `{}` included, by
+    /// their index (`"0"`, `"1"`, ...). Specs are applied as `format!` would,
+    /// except that [`Value::Display`] values are padded like strings: left-aligned
+    /// by default and with the fill character even with the `0` flag.
+    pub fn render<'a>(
+        &self,
+        lookup: impl Fn(&str) -> Option<Value<'a>>,
+    ) -> Result<String, RenderError> {
+        let mut output = String::new();
+
+        for segment in &self.with_explicit_positions().segments {
+            let placeholder = match segment {
+                Segment::Literal(literal) => {
+                    output.push_str(&literal.replace("{{", "{").replace("}}", "}"));
+                    continue;
+                }
+                Segment::Placeholder(placeholder) => placeholder,
+            };
+
+            let byte_range = placeholder.byte_range.clone();
+            let count = |count: &Option<Count>| match count {
+                Some(Count::Integer(count)) => Ok(Some(*count)),
+                Some(Count::Argument(argument)) => {
+                    let name = argument.to_string();
+                    let value = lookup(&name).ok_or_else(|| RenderError::Missing {
+                        name: name.clone(),
+                        byte_range: byte_range.clone(),
+                    })?;
+
+                    match value {
+                        Value::UInt(count) => usize::try_from(count).ok(),
+                        Value::Int { value, .. } => usize::try_from(value).ok(),
+                        _ => None,
+                    }
+                    .map(Some)
+                    .ok_or(RenderError::InvalidCount {
+                        name,
+                        byte_range: byte_range.clone(),
+                    })
+                }
+                None => Ok(None),
+            };
+
+            let spec = &placeholder.spec;
+            let width = count(&spec.width)?.unwrap_or(0);
+            let precision = count(&spec.precision)?;
+
+            let mut name = placeholder.argument.to_string();
+            for member in &placeholder.members {
+                name = format!("{name}.{member}");
+            }
+            let value = lookup(&name).ok_or_else(|| RenderError::Missing {
+                name: name.clone(),
+                byte_range: byte_range.clone(),
+            })?;
+
+            // Numbers are zero-padded by `format!` itself, which ignores fill and
+            // alignment; everything else is padded below
+            let numeric = matches!(value, Value::Int { .. } | Value::UInt(_) | Value::Float(_));
+            let zero_pad = spec.zero_pad && numeric;
+            let flags = Flags {
+                plus: spec.sign == Some(Sign::Plus),
+                alternate: spec.alternate,
+                width: if zero_pad { width } else { 0 },
+                precision,
+            };
+
+            let text = value
+                .format(spec.format_trait, &flags)
+                .ok_or(RenderError::Unsupported {
+                    name,
+                    format_trait: spec.format_trait,
+                    byte_range,
+                })?;
+
+            // Like `format!`, the `Debug` impls of strings and characters ignore the width
+            let debug = !matches!(spec.format_trait, FormatTrait::Display);
+            let width = match value {
+                Value::Str(_) | Value::Char(_) if debug => 0,
+                _ => width,
+            };
+
+            let padding = width.saturating_sub(text.chars().count());
+            let align = spec.align.unwrap_or(match numeric {
+                true => Align::Right,
+                false => Align::Left,
+            });
+            let (before, after) = match align {
+                _ if zero_pad => (0, 0),
+                Align::Left => (0, padding),
+                Align::Center => (padding / 2, padding - padding / 2),
+                Align::Right => (padding, 0),
+            };
+
+            let fill = spec.fill.unwrap_or(' ');
+            output.extend(core::iter::repeat_n(fill, before));
+            output.push_str(&text);
+            output.extend(core::iter::repeat_n(fill, after));
+        }
+
+        Ok(output)
+    }
+
+    /// Renders the format string at runtime with the values in `map`, see
+    /// [`FormatString::render`].
+    #[cfg(feature = "std")]
+    pub fn render_map<K, S>(&self, map: &HashMap<K, Value<'_>, S>) -> Result<String, RenderError>
+    where
+        K: Borrow<str> + Hash + Eq,
+        S: BuildHasher,
+    {
+        self.render(|name| map.get(name).copied())
+    }
+}
+
+/// The flags of a spec that `format!` applies to the value itself.
+struct Flags {
+    plus: bool,
+    alternate: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+/// Formats `$value` with the `$format_trait` suffix (e.g. `"x"`) and `$flags`.
+macro_rules! format_with {
+    ($value:expr, $format_trait:literal, $flags:expr) => {{
+        let (value, flags) = ($value, $flags);
+        let width = flags.width;
+        Some(match (flags.plus, flags.alternate, flags.precision) {
+            (false, false, None) => format!(concat!("{:0w$", $format_trait, "}"), value, w = width),
+            (true, false, None) => format!(concat!("{:+0w$", $format_trait, "}"), value, w = width),
+            (false, true, None) => format!(concat!("{:#0w$", $format_trait, "}"), value, w = width),
+            (true, true, None) => format!(concat!("{:+#0w$", $format_trait, "}"), value, w = width),
+            (false, false, Some(p)) => {
+                format!(
+                    concat!("{:0w$.p$", $format_trait, "}"),
+                    value,
+                    w = width,
+                    p = p
+                )
+            }
+            (true, false, Some(p)) => {
+                format!(
+                    concat!("{:+0w$.p$", $format_trait, "}"),
+                    value,
+                    w = width,
+                    p = p
+                )
+            }
+            (false, true, Some(p)) => {
+                format!(
+                    concat!("{:#0w$.p$", $format_trait, "}"),
+                    value,
+                    w = width,
+                    p = p
+                )
+            }
+            (true, true, Some(p)) => {
+                format!(
+                    concat!("{:+#0w$.p$", $format_trait, "}"),
+                    value,
+                    w = width,
+                    p = p
+                )
+            }
+        })
+    }};
+}
+
+/// Formats `$value` with any of the `Debug` format traits.
+macro_rules! format_debug {
+    ($value:expr, $format_trait:expr, $flags:expr) => {
+        match $format_trait {
+            FormatTrait::Debug => format_with!($value, "?", $flags),
+            FormatTrait::LowerHexDebug => format_with!($value, "x?", $flags),
+            FormatTrait::UpperHexDebug => format_with!($value, "X?", $flags),
+            _ => None,
+        }
+    };
+}
+
+/// Formats the integer `$value` with any format trait but `Pointer`.
+macro_rules! format_integer {
+    ($value:expr, $format_trait:expr, $flags:expr) => {
+        match $format_trait {
+            FormatTrait::Display => format_with!($value, "", $flags),
+            FormatTrait::Octal => format_with!($value, "o", $flags),
+            FormatTrait::LowerHex => format_with!($value, "x", $flags),
+            FormatTrait::UpperHex => format_with!($value, "X", $flags),
+            FormatTrait::Binary => format_with!($value, "b", $flags),
+            FormatTrait::LowerExp => format_with!($value, "e", $flags),
+            FormatTrait::UpperExp => format_with!($value, "E", $flags),
+            format_trait => format_debug!($value, format_trait, $flags),
+        }
+    };
+}
+
+impl Value<'_> {
+    /// Formats the value, or returns `None` if it does not implement `format_trait`.
+    fn format(&self, format_trait: FormatTrait, flags: &Flags) -> Option<String> {
+        match (*self, format_trait) {
+            (Value::Display(value), FormatTrait::Display) => format_with!(value, "", flags),
+            (Value::Display(_), _) => None,
+            (_, FormatTrait::Pointer) => None,
+
+            (Value::Str(value), FormatTrait::Display) => format_with!(value, "", flags),
+            (Value::Str(value), format_trait) => format_debug!(value, format_trait, flags),
+            (Value::Char(value), FormatTrait::Display) => format_with!(value, "", flags),
+            (Value::Char(value), format_trait) => format_debug!(value, format_trait, flags),
+            (Value::Bool(value), FormatTrait::Display) => format_with!(value, "", flags),
+            (Value::Bool(value), format_trait) => format_debug!(value, format_trait, flags),
+
+            (Value::Float(value), FormatTrait::Display) => format_with!(value, "", flags),
+            (Value::Float(value), FormatTrait::LowerExp) => format_with!(value, "e", flags),
+            (Value::Float(value), FormatTrait::UpperExp) => format_with!(value, "E", flags),
+            (Value::Float(value), format_trait) => format_debug!(value, format_trait, flags),
+
+            // Negative values are printed in two's complement of their type's width
+            (
+                Value::Int { value, bits },
+                FormatTrait::Octal
+                | FormatTrait::LowerHex
+                | FormatTrait::UpperHex
+                | FormatTrait::Binary
+                | FormatTrait::LowerHexDebug
+                | FormatTrait::UpperHexDebug,
+            ) => {
+                let mask = u64::MAX.checked_shr(64 - bits.min(64)).unwrap_or(u64::MAX);
+                format_integer!(value as u64 & mask, format_trait, flags)
+            }
+            (Value::Int { value, .. }, format_trait) => {
+                format_integer!(value, format_trait, flags)
+            }
+            (Value::UInt(value), format_trait) => format_integer!(value, format_trait, flags),
+        }
+    }
+}
+
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Str(value) => value.fmt(f),
+            Value::Char(value) => value.fmt(f),
+            Value::Bool(value) => value.fmt(f),
+            Value::Int { value, .. } => value.fmt(f),
+            Value::UInt(value) => value.fmt(f),
+            Value::Float(value) => value.fmt(f),
+            Value::Display(value) => value.fmt(f),
+        }
+    }
+}
+
+impl fmt::Debug for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Str(value) => f.debug_tuple("Str").field(value).finish(),
+            Value::Char(value) => f.debug_tuple("Char").field(value).finish(),
+            Value::Bool(value) => f.debug_tuple("Bool").field(value).finish(),
+            Value::Int { value, bits } => f
+                .debug_struct("Int")
+                .field("value", value)
+                .field("bits", bits)
+                .finish(),
+            Value::UInt(value) => f.debug_tuple("UInt").field(value).finish(),
+            Value::Float(value) => f.debug_tuple("Float").field(value).finish(),
+            Value::Display(value) => f.debug_tuple("Display").field(&value.to_string()).finish(),
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(impl From<$ty> for Value<'_> {
+            fn from(value: $ty) -> Self {
+                Value::$variant(value.into())
+            }
+        })*
+    };
+}
+
+impl_from! {
+    char => Char,
+    bool => Bool,
+    u8 => UInt, u16 => UInt, u32 => UInt, u64 => UInt,
+    f32 => Float, f64 => Float,
+}
+
+/// Implements `From` for signed integers, recording the width of their type.
+macro_rules! impl_from_signed {
+    ($($ty:ty),* $(,)?) => {
+        $(impl From<$ty> for Value<'_> {
+            fn from(value: $ty) -> Self {
+                Value::Int {
+                    value: value as i64,
+                    bits: <$ty>::BITS,
+                }
+            }
+        })*
+    };
+}
+
+impl_from_signed!(i8, i16, i32, i64, isize);
+
+impl From<usize> for Value<'_> {
+    fn from(value: usize) -> Self {
+        Value::UInt(value as u64)
+    }
+}
+
+impl<'a> From<&'a str> for Value<'a> {
+    fn from(value: &'a str) -> Self {
+        Value::Str(value)
+    }
+}
+
+impl<'a> From<&'a String> for Value<'a> {
+    fn from(value: &'a String) -> Self {
+        Value::Str(value)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{RenderError, Value};
+    use crate::{FormatString, FormatTrait};
+
+    fn render(text: &str, values: &[(&str, Value)]) -> Result<String, RenderError> {
+        let map = values.iter().copied().collect::<HashMap<_, _>>();
+        FormatString::parse(text).unwrap().render_map(&map)
+    }
+
+    #[test]
+    fn test_named_and_positional() {
+        assert_eq!(
+            render(
+                "{{{name}}} {} {0:?} {}",
+                &[("name", "id".into()), ("0", 'a'.into()), ("1", 1.5.into())]
+            ),
+            Ok("{id} a 'a' 1.5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_member_paths() {
+        assert_eq!(
+            render("{request.id:>4}", &[("request.id", 7.into())]),
+            Ok("   7".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fill_and_counts() {
+        assert_eq!(
+            render(
+                "[{name:*^width$}] [{:.*}]",
+                &[
+                    ("name", "ab".into()),
+                    ("width", 6usize.into()),
+                    ("0", 2.into()),
+                    ("1", 2.71.into())
+                ]
+            ),
+            Ok("[**ab**] [2.71]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_display_values() {
+        let path = std::path::Path::new("/tmp");
+        let display = path.display();
+        let map = HashMap::from([("path", Value::Display(&display))]);
+
+        assert_eq!(
+            FormatString::parse("{path:>6}|{path:6}|{path:*>08}|")
+                .unwrap()
+                .render_map(&map),
+            Ok("  /tmp|/tmp  |****/tmp|".to_string())
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            render("a {missing}", &[]),
+            Err(RenderError::Missing {
+                name: "missing".to_string(),
+                byte_range: 2..11
+            })
+        );
+        assert_eq!(
+            render("{:w$}", &[("0", 1.into()), ("w", (-1).into())]),
+            Err(RenderError::InvalidCount {
+                name: "w".to_string(),
+                byte_range: 0..5
+            })
+        );
+        assert_eq!(
+            render("{:x}", &[("0", 1.5.into())]),
+            Err(RenderError::Unsupported {
+                name: "0".to_string(),
+                format_trait: FormatTrait::LowerHex,
+                byte_range: 0..4
+            })
+        );
+        assert_eq!(
+            render("{:x}", &[("0", 1.5.into())])
+                .unwrap_err()
+                .to_string(),
+            "argument `0` cannot be formatted with `{:x}`"
+        );
+    }
+}
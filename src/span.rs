@@ -0,0 +1,136 @@
+use std::ops::Range;
+
+use proc_macro2::Span;
+use syn::LitStr;
+
+/// Returns the span of `range`, a byte range of the literal's value, inside the literal.
+///
+/// Falls back to the span of the whole literal when the compiler cannot create
+/// sub-spans (e.g. on stable) or the range cannot be mapped to the source.
+pub fn subspan(literal: &LitStr, range: Range<usize>) -> Span {
+    let token = literal.token();
+
+    source_range(&token.to_string(), range)
+        .and_then(|range| token.subspan(range))
+        .unwrap_or_else(|| literal.span())
+}
+
+/// Maps a byte range of a string literal's value to a byte range of its source,
+/// accounting for the quotes, raw string hashes and escape sequences.
+fn source_range(source: &str, range: Range<usize>) -> Option<Range<usize>> {
+    let offsets = value_offsets(source)?;
+    let start = offsets.get(range.start)?.start;
+    let end = match range.end.checked_sub(1) {
+        Some(last) if range.end > range.start => offsets.get(last)?.end,
+        _ => start,
+    };
+
+    Some(start..end)
+}
+
+/// For each byte of the literal's value, the source range of the character
+/// (or escape sequence) that produced it.
+fn value_offsets(source: &str) -> Option<Vec<Range<usize>>> {
+    let mut offsets = Vec::new();
+
+    if let Some(raw) = source.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let start = 1 + hashes + 1;
+        let end = source.rfind('"')?;
+
+        for (index, c) in source.get(start..end)?.char_indices() {
+            let range = start + index..start + index + c.len_utf8();
+            offsets.extend(std::iter::repeat_n(range, c.len_utf8()));
+        }
+
+        return Some(offsets);
+    }
+
+    let end = source.rfind('"')?;
+    let mut chars = source.get(..end)?.char_indices().skip(1).peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let value = if c != '\\' {
+            Some(c)
+        } else {
+            match chars.next()?.1 {
+                'x' => {
+                    let digits = [chars.next()?.1, chars.next()?.1];
+                    let code = u32::from_str_radix(&String::from_iter(digits), 16).ok()?;
+                    char::from_u32(code)
+                }
+                'u' => {
+                    let mut code = String::new();
+                    while let Some((_, c)) = chars.next().filter(|&(_, c)| c != '}') {
+                        if c != '{' && c != '_' {
+                            code.push(c);
+                        }
+                    }
+                    char::from_u32(u32::from_str_radix(&code, 16).ok()?)
+                }
+                // A line continuation skips the newline and leading whitespace
+                '\n' => {
+                    while chars.next_if(|&(_, c)| c.is_whitespace()).is_some() {}
+                    None
+                }
+                'n' => Some('\n'),
+                'r' => Some('\r'),
+                't' => Some('\t'),
+                '0' => Some('\0'),
+                c => Some(c),
+            }
+        };
+
+        if let Some(value) = value {
+            let next = chars.peek().map_or(end, |&(index, _)| index);
+            offsets.extend(std::iter::repeat_n(start..next, value.len_utf8()));
+        }
+    }
+
+    Some(offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{source_range, subspan};
+
+    /// Returns the source text covered by `range` of the literal's value.
+    fn source(literal: &str, range: std::ops::Range<usize>) -> &str {
+        &literal[source_range(literal, range).unwrap()]
+    }
+
+    #[test]
+    fn test_plain_literal() {
+        assert_eq!(source(r#""a {b} c""#, 2..5), "{b}");
+    }
+
+    #[test]
+    fn test_escapes() {
+        let literal = r#""\t\"{x}\u{e9}\x41{y}""#;
+        let value = "\t\"{x}\u{e9}\x41{y}";
+
+        assert_eq!(source(literal, 2..5), "{x}");
+        assert_eq!(source(literal, 5..7), "\\u{e9}");
+        assert_eq!(source(literal, value.len() - 3..value.len()), "{y}");
+    }
+
+    #[test]
+    fn test_line_continuation() {
+        let literal = "\"a \\\n     {b}\"";
+        assert_eq!(source(literal, 2..5), "{b}");
+    }
+
+    #[test]
+    fn test_raw_literal() {
+        assert_eq!(source(r###"r#"{"a"}"#"###, 0..5), r#"{"a"}"#);
+    }
+
+    #[test]
+    fn test_subspan() {
+        let literal = syn::parse_str::<syn::LitStr>(r#"  "\n{value}""#).unwrap();
+        let span = subspan(&literal, 1..8);
+
+        assert_eq!(span.start().column, 5);
+        assert_eq!(span.end().column, 12);
+    }
+}
@@ -0,0 +1,622 @@
+#[cfg(feature = "alloc")]
+use core::fmt;
+
+use crate::pieces::{is_identifier_continue, is_identifier_start, parse_integer};
+use crate::{FormatError, FormatErrorKind, RawArgument};
+
+#[cfg(feature = "alloc")]
+use crate::Argument;
+
+/// The parsed `std::fmt` spec following the `:` of a placeholder.
+///
+/// ```text
+/// format_spec := [[fill]align][sign]['#']['0'][width]['.' precision]type
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FormatSpec {
+    /// The fill character, only present together with an alignment.
+    pub fill: Option<char>,
+
+    /// `<`, `^` or `>`.
+    pub align: Option<Align>,
+
+    /// `+` or `-`.
+    pub sign: Option<Sign>,
+
+    /// `#`: the alternate form.
+    pub alternate: bool,
+
+    /// `0`: sign-aware zero padding.
+    pub zero_pad: bool,
+
+    /// The minimum width.
+    pub width: Option<Count>,
+
+    /// The precision following `.`.
+    pub precision: Option<Count>,
+
+    /// The formatting trait selected by the type.
+    pub format_trait: FormatTrait,
+}
+
+/// A format spec whose counts borrow argument names from the format string, parsed
+/// without allocating.
+#[cfg_attr(
+    feature = "alloc",
+    doc = "",
+    doc = "[`RawSpec::into_owned`] converts it into a [`FormatSpec`]."
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RawSpec<'a> {
+    /// The fill character, only present together with an alignment.
+    pub fill: Option<char>,
+
+    /// `<`, `^` or `>`.
+    pub align: Option<Align>,
+
+    /// `+` or `-`.
+    pub sign: Option<Sign>,
+
+    /// `#`: the alternate form.
+    pub alternate: bool,
+
+    /// `0`: sign-aware zero padding.
+    pub zero_pad: bool,
+
+    /// The minimum width.
+    pub width: Option<RawCount<'a>>,
+
+    /// The precision following `.`.
+    pub precision: Option<RawCount<'a>>,
+
+    /// The formatting trait selected by the type.
+    pub format_trait: FormatTrait,
+}
+
+/// The alignment of a padded value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    /// `<`
+    Left,
+
+    /// `^`
+    Center,
+
+    /// `>`
+    Right,
+}
+
+/// The sign flag of a spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sign {
+    /// `+`
+    Plus,
+
+    /// `-`
+    Minus,
+}
+
+/// A width or precision.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Count {
+    /// A literal count, e.g. `5`.
+    Integer(usize),
+
+    /// A count read from an argument, e.g. `width$` or `1$`.
+    ///
+    /// A `.*` precision is represented as [`Argument::Implicit`].
+    Argument(Argument),
+}
+
+/// A width or precision of a [`RawSpec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawCount<'a> {
+    /// A literal count, e.g. `5`.
+    Integer(usize),
+
+    /// A count read from an argument, e.g. `width$` or `1$`.
+    ///
+    /// A `.*` precision is represented as [`RawArgument::Implicit`].
+    Argument(RawArgument<'a>),
+}
+
+/// The formatting trait selected by the type of a spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FormatTrait {
+    /// No type: `Display`.
+    #[default]
+    Display,
+
+    /// `?`
+    Debug,
+
+    /// `x?`
+    LowerHexDebug,
+
+    /// `X?`
+    UpperHexDebug,
+
+    /// `o`
+    Octal,
+
+    /// `x`
+    LowerHex,
+
+    /// `X`
+    UpperHex,
+
+    /// `p`
+    Pointer,
+
+    /// `b`
+    Binary,
+
+    /// `e`
+    LowerExp,
+
+    /// `E`
+    UpperExp,
+}
+
+impl FormatTrait {
+    /// The type written in the spec to select the trait.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FormatTrait::Display => "",
+            FormatTrait::Debug => "?",
+            FormatTrait::LowerHexDebug => "x?",
+            FormatTrait::UpperHexDebug => "X?",
+            FormatTrait::Octal => "o",
+            FormatTrait::LowerHex => "x",
+            FormatTrait::UpperHex => "X",
+            FormatTrait::Pointer => "p",
+            FormatTrait::Binary => "b",
+            FormatTrait::LowerExp => "e",
+            FormatTrait::UpperExp => "E",
+        }
+    }
+
+    fn from_type(ty: &str) -> Option<FormatTrait> {
+        [
+            FormatTrait::Display,
+            FormatTrait::Debug,
+            FormatTrait::LowerHexDebug,
+            FormatTrait::UpperHexDebug,
+            FormatTrait::Octal,
+            FormatTrait::LowerHex,
+            FormatTrait::UpperHex,
+            FormatTrait::Pointer,
+            FormatTrait::Binary,
+            FormatTrait::LowerExp,
+            FormatTrait::UpperExp,
+        ]
+        .into_iter()
+        .find(|format_trait| format_trait.as_str() == ty)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FormatSpec {
+    /// Parses the text following `:`, which starts at byte `offset` of the format string.
+    pub fn parse(text: &str, offset: usize) -> Result<FormatSpec, FormatError> {
+        RawSpec::parse(text, offset).map(RawSpec::into_owned)
+    }
+
+    /// Returns true if the spec is empty, i.e. equivalent to `{}`.
+    pub fn is_empty(&self) -> bool {
+        *self == FormatSpec::default()
+    }
+}
+
+impl<'a> RawSpec<'a> {
+    /// Parses the text following `:`, which starts at byte `offset` of the format string.
+    pub fn parse(text: &'a str, offset: usize) -> Result<RawSpec<'a>, FormatError> {
+        let mut cursor = Cursor {
+            text,
+            offset,
+            position: 0,
+        };
+        let mut spec = RawSpec::default();
+
+        // The fill character is only recognised when followed by an alignment
+        let mut chars = text.chars();
+        if let (Some(fill), Some(align)) = (chars.next(), chars.next().and_then(Align::from_char)) {
+            cursor.bump(fill);
+            cursor.bump(align.as_char());
+            spec.fill = Some(fill);
+            spec.align = Some(align);
+        } else if let Some(align) = cursor.peek().and_then(Align::from_char) {
+            cursor.bump(align.as_char());
+            spec.align = Some(align);
+        }
+
+        if cursor.eat('+') {
+            spec.sign = Some(Sign::Plus);
+        } else if cursor.eat('-') {
+            spec.sign = Some(Sign::Minus);
+        }
+
+        spec.alternate = cursor.eat('#');
+
+        // `0$` is a width taken from the first positional argument, not the zero flag
+        if cursor.rest().starts_with('0') && !cursor.rest().starts_with("0$") {
+            cursor.bump('0');
+            spec.zero_pad = true;
+        }
+
+        spec.width = cursor.count()?;
+
+        if cursor.eat('.') {
+            spec.precision = if cursor.eat('*') {
+                Some(RawCount::Argument(RawArgument::Implicit))
+            } else {
+                cursor.count()?
+            };
+        }
+
+        let start = cursor.position;
+        if let Some(rest) = cursor
+            .rest()
+            .strip_prefix(['x', 'X'])
+            .filter(|rest| rest.starts_with('?'))
+        {
+            cursor.position = text.len() - rest.len() + 1;
+        } else if !cursor.eat('?') {
+            cursor.identifier();
+        }
+
+        let ty = &text[start..cursor.position];
+        spec.format_trait = FormatTrait::from_type(ty).ok_or_else(|| {
+            FormatError::new(
+                FormatErrorKind::UnknownFormatTrait,
+                ty,
+                offset + start..offset + cursor.position,
+            )
+        })?;
+
+        // Like `format_args!`, whitespace is allowed before the closing brace
+        while let Some(c) = cursor.peek().filter(|c| c.is_whitespace()) {
+            cursor.bump(c);
+        }
+
+        if let Some(c) = cursor.peek() {
+            let position = cursor.position;
+            return Err(FormatError::new(
+                FormatErrorKind::InvalidSpec(c),
+                &text[position..position + c.len_utf8()],
+                offset + position..offset + position + c.len_utf8(),
+            ));
+        }
+
+        Ok(spec)
+    }
+
+    /// Converts the spec into an owned [`FormatSpec`].
+    #[cfg(feature = "alloc")]
+    pub fn into_owned(self) -> FormatSpec {
+        FormatSpec {
+            fill: self.fill,
+            align: self.align,
+            sign: self.sign,
+            alternate: self.alternate,
+            zero_pad: self.zero_pad,
+            width: self.width.map(RawCount::into_owned),
+            precision: self.precision.map(RawCount::into_owned),
+            format_trait: self.format_trait,
+        }
+    }
+}
+
+impl RawCount<'_> {
+    /// Converts the count into an owned [`Count`].
+    #[cfg(feature = "alloc")]
+    pub fn into_owned(self) -> Count {
+        match self {
+            RawCount::Integer(count) => Count::Integer(count),
+            RawCount::Argument(argument) => Count::Argument(argument.into_owned()),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for FormatSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(align) = self.align {
+            if let Some(fill) = self.fill {
+                write!(f, "{fill}")?;
+            }
+            write!(f, "{}", align.as_char())?;
+        }
+
+        match self.sign {
+            Some(Sign::Plus) => write!(f, "+")?,
+            Some(Sign::Minus) => write!(f, "-")?,
+            None => {}
+        }
+
+        if self.alternate {
+            write!(f, "#")?;
+        }
+
+        if self.zero_pad {
+            write!(f, "0")?;
+        }
+
+        if let Some(width) = &self.width {
+            write!(f, "{width}")?;
+        }
+
+        if let Some(precision) = &self.precision {
+            write!(f, ".{precision}")?;
+        }
+
+        write!(f, "{}", self.format_trait.as_str())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Count::Integer(count) => write!(f, "{count}"),
+            Count::Argument(Argument::Implicit) => write!(f, "*"),
+            Count::Argument(argument) => write!(f, "{argument}$"),
+        }
+    }
+}
+
+impl Align {
+    fn from_char(c: char) -> Option<Align> {
+        match c {
+            '<' => Some(Align::Left),
+            '^' => Some(Align::Center),
+            '>' => Some(Align::Right),
+            _ => None,
+        }
+    }
+
+    fn as_char(self) -> char {
+        match self {
+            Align::Left => '<',
+            Align::Center => '^',
+            Align::Right => '>',
+        }
+    }
+}
+
+/// A position in the spec text.
+struct Cursor<'a> {
+    text: &'a str,
+    offset: usize,
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self, c: char) {
+        self.position += c.len_utf8();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let matches = self.peek() == Some(c);
+        if matches {
+            self.bump(c);
+        }
+        matches
+    }
+
+    fn integer(&mut self) -> Result<Option<usize>, FormatError> {
+        let digits = self.rest().len()
+            - self
+                .rest()
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len();
+
+        if digits == 0 {
+            return Ok(None);
+        }
+
+        let value = parse_integer(&self.rest()[..digits], self.offset + self.position)?;
+        self.position += digits;
+        Ok(Some(value))
+    }
+
+    fn identifier(&mut self) -> Option<&'a str> {
+        let start = self.position;
+        let first = self.peek().filter(|&c| is_identifier_start(c))?;
+        self.bump(first);
+
+        while let Some(c) = self.peek().filter(|&c| is_identifier_continue(c)) {
+            self.bump(c);
+        }
+
+        Some(&self.text[start..self.position])
+    }
+
+    /// Parses `integer`, `integer$` or `identifier$`, leaving a bare identifier for the type.
+    fn count(&mut self) -> Result<Option<RawCount<'a>>, FormatError> {
+        let start = self.position;
+
+        if let Some(integer) = self.integer()? {
+            return Ok(Some(if self.eat('$') {
+                RawCount::Argument(RawArgument::Positional(integer))
+            } else {
+                RawCount::Integer(integer)
+            }));
+        }
+
+        let Some(name) = self.identifier() else {
+            return Ok(None);
+        };
+
+        if self.eat('$') {
+            if name == "_" {
+                return Err(FormatError::new(
+                    FormatErrorKind::Underscore,
+                    name,
+                    self.offset + start..self.offset + start + 1,
+                ));
+            }
+            return Ok(Some(RawCount::Argument(RawArgument::Named(name))));
+        }
+
+        self.position = start;
+        Ok(None)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::{Align, Count, FormatSpec, FormatTrait, RawCount, RawSpec, Sign};
+    use crate::{Argument, FormatErrorKind, RawArgument};
+
+    fn parse(text: &str) -> FormatSpec {
+        FormatSpec::parse(text, 0).unwrap()
+    }
+
+    fn error(text: &str) -> (String, std::ops::Range<usize>) {
+        let error = FormatSpec::parse(text, 0).unwrap_err();
+        (error.message().to_string(), error.byte_range().clone())
+    }
+
+    #[test]
+    fn test_full_spec() {
+        assert_eq!(
+            parse("*^+#010.3e"),
+            FormatSpec {
+                fill: Some('*'),
+                align: Some(Align::Center),
+                sign: Some(Sign::Plus),
+                alternate: true,
+                zero_pad: true,
+                width: Some(Count::Integer(10)),
+                precision: Some(Count::Integer(3)),
+                format_trait: FormatTrait::LowerExp,
+            }
+        );
+    }
+
+    #[test]
+    fn test_fill_and_align() {
+        assert_eq!(parse(">5").align, Some(Align::Right));
+        assert_eq!(parse(">5").fill, None);
+        assert_eq!(parse("<<").fill, Some('<'));
+        assert_eq!(parse("ü^").fill, Some('ü'));
+        assert_eq!(parse("_<").width, None);
+    }
+
+    #[test]
+    fn test_counts() {
+        assert_eq!(
+            parse("0$").width,
+            Some(Count::Argument(Argument::Positional(0)))
+        );
+        assert!(!parse("0$").zero_pad);
+        assert_eq!(
+            parse("width$").width,
+            Some(Count::Argument(Argument::Named("width".to_string())))
+        );
+        assert_eq!(
+            parse(".*").precision,
+            Some(Count::Argument(Argument::Implicit))
+        );
+        assert_eq!(
+            parse("1.prec$").precision,
+            Some(Count::Argument(Argument::Named("prec".to_string())))
+        );
+        assert_eq!(parse(".").precision, None);
+    }
+
+    #[test]
+    fn test_format_traits() {
+        assert_eq!(parse("").format_trait, FormatTrait::Display);
+        assert_eq!(parse("#?").format_trait, FormatTrait::Debug);
+        assert_eq!(parse("x?").format_trait, FormatTrait::LowerHexDebug);
+        assert_eq!(parse("08X").format_trait, FormatTrait::UpperHex);
+        assert_eq!(parse("p").format_trait, FormatTrait::Pointer);
+        assert_eq!(parse("x").width, None);
+    }
+
+    #[test]
+    fn test_invalid_specs() {
+        assert_eq!(
+            error(">>>5"),
+            (
+                "invalid format spec: expected `}`, found `>`".to_string(),
+                2..3
+            )
+        );
+        assert_eq!(
+            error("x y"),
+            (
+                "invalid format spec: expected `}`, found `y`".to_string(),
+                2..3
+            )
+        );
+        assert_eq!(
+            error("_$"),
+            (
+                "invalid format string: invalid argument name `_`".to_string(),
+                0..1
+            )
+        );
+        assert_eq!(
+            error("+-5"),
+            (
+                "invalid format spec: expected `}`, found `-`".to_string(),
+                1..2
+            )
+        );
+        assert_eq!(
+            error(".70000"),
+            (
+                "invalid format string: integer `70000` does not fit into the type `u16` whose \
+                 range is `0..=65535`"
+                    .to_string(),
+                1..6
+            )
+        );
+        assert_eq!(
+            error("5foo"),
+            ("unknown format trait `foo`".to_string(), 1..4)
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for spec in ["", "?", "*^+#010.3e", ">width$.2$", "0$", ".*", "<<", "#x?"] {
+            assert_eq!(parse(spec).to_string(), spec);
+        }
+
+        // Trailing whitespace is dropped
+        assert_eq!(parse(">5 \t").to_string(), ">5");
+        assert_eq!(parse(" ").to_string(), "");
+    }
+
+    #[test]
+    fn test_raw_spec() {
+        let text = "<width$.1$x";
+        let spec = RawSpec::parse(text, 0).unwrap();
+        assert_eq!(
+            spec.width,
+            Some(RawCount::Argument(RawArgument::Named("width")))
+        );
+        assert_eq!(
+            spec.precision,
+            Some(RawCount::Argument(RawArgument::Positional(1)))
+        );
+        assert_eq!(spec.into_owned(), parse(text));
+
+        let error = RawSpec::parse("5foo", 0).unwrap_err();
+        assert_eq!(error.kind(), FormatErrorKind::UnknownFormatTrait);
+    }
+}
@@ -0,0 +1,401 @@
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{Fields, Member};
+
+use crate::{Argument, Interpolate};
+
+impl Interpolate<'_> {
+    /// Checks that every placeholder, including `width$`/`precision$` counts, refers
+    /// to a field of the variant, to an extra argument if any were given, or to a
+    /// constant it captures (see [`Interpolate::captures`]).
+    ///
+    /// Reports unknown field names (with a suggestion for close matches), tuple
+    /// indices out of range and placeholders that do not fit the variant's kind,
+    /// each at the span of the offending placeholder. Unused extra arguments and
+    /// `.field` shorthands naming missing fields are reported at their own spans.
+    pub fn validate(&self) -> syn::Result<()> {
+        let mut errors = Vec::new();
+        let format = self.format.with_explicit_positions();
+
+        for (placeholder, span) in format.placeholders().zip(&self.spans) {
+            for argument in placeholder.arguments() {
+                let index = match argument {
+                    Argument::Positional(index) => Some(*index),
+                    _ => None,
+                };
+
+                let result = match index {
+                    Some(index) if !self.args.is_empty() => self.check_arg(index),
+                    _ if self.extra_arg(argument).is_some() => Ok(()),
+                    _ if self.captures(argument) => Ok(()),
+                    _ => self.check(argument, index),
+                };
+                if let Err(message) = result {
+                    errors.push(syn::Error::new(*span, message));
+                }
+            }
+        }
+
+        let positions = self.format.positional_arguments();
+        for index in positions.unused(self.args.len()) {
+            errors.push(syn::Error::new(
+                self.args[index].span(),
+                "argument never used",
+            ));
+        }
+
+        for (name, _) in &self.named_args {
+            let argument = Argument::Named(name.unraw().to_string());
+            let projected = self
+                .projections
+                .iter()
+                .any(|projection| projection.root == argument);
+            if !self.identifiers.contains(&argument) && !projected {
+                errors.push(syn::Error::new(name.span(), "named argument never used"));
+            }
+        }
+
+        for member in &self.shorthand_fields {
+            if let Err(message) = self.check_shorthand(member) {
+                errors.push(syn::Error::new(member.span(), message));
+            }
+        }
+
+        errors
+            .into_iter()
+            .reduce(|mut errors, error| {
+                errors.combine(error);
+                errors
+            })
+            .map_or(Ok(()), Err)
+    }
+
+    /// Checks that an extra argument exists for the positional argument at `index`.
+    fn check_arg(&self, index: usize) -> Result<(), String> {
+        match self.args.len() {
+            len if index < len => Ok(()),
+            1 => Err(format!(
+                "there is 1 argument, but the message refers to argument {index}"
+            )),
+            len => Err(format!(
+                "there are {len} arguments, but the message refers to argument {index}"
+            )),
+        }
+    }
+
+    /// Checks that a `.field` shorthand names a field of the variant.
+    fn check_shorthand(&self, member: &Member) -> Result<(), String> {
+        let variant = &self.variant.ident;
+        let exists = self
+            .variant
+            .fields
+            .members()
+            .any(|field| match (&field, member) {
+                (Member::Named(field), Member::Named(name)) => field.unraw() == *name,
+                (field, member) => field == member,
+            });
+
+        match member {
+            _ if exists => Ok(()),
+            Member::Named(name) => {
+                let names = self
+                    .variant
+                    .fields
+                    .iter()
+                    .flat_map(|field| &field.ident)
+                    .map(|ident| ident.unraw().to_string())
+                    .collect::<Vec<_>>();
+
+                let mut message = format!("`{variant}` has no field named `{name}`");
+                if let Some(similar) = similar(&name.to_string(), &names) {
+                    message.push_str(&format!("; did you mean `{similar}`?"));
+                }
+                Err(message)
+            }
+            Member::Unnamed(index) => Err(format!("`{variant}` has no field `{}`", index.index)),
+        }
+    }
+
+    /// Checks a single argument, `index` being its resolved position if it is positional.
+    fn check(&self, argument: &Argument, index: Option<usize>) -> Result<(), String> {
+        let variant = &self.variant.ident;
+
+        match (&self.variant.fields, argument, index) {
+            (Fields::Unit, Argument::Named(name), _) => Err(format!(
+                "`{variant}` has no fields, but the message refers to `{name}`"
+            )),
+            (Fields::Unit, _, _) => Err(format!(
+                "`{variant}` has no fields, but the message has a positional placeholder"
+            )),
+            (Fields::Named(fields), Argument::Named(name), _) => {
+                let names = fields
+                    .named
+                    .iter()
+                    .flat_map(|field| &field.ident)
+                    .map(|ident| ident.unraw().to_string())
+                    .collect::<Vec<_>>();
+
+                if names.contains(name) {
+                    return Ok(());
+                }
+
+                let mut message = format!("`{variant}` has no field named `{name}`");
+                if let Some(similar) = similar(name, &names) {
+                    message.push_str(&format!("; did you mean `{similar}`?"));
+                }
+                Err(message)
+            }
+            (Fields::Named(_), _, _) => Err(format!(
+                "positional placeholder `{{{}}}` used on `{variant}`, which has named fields; \
+                 refer to the fields by name instead",
+                match argument {
+                    Argument::Positional(index) => index.to_string(),
+                    _ => String::new(),
+                }
+            )),
+            (Fields::Unnamed(fields), Argument::Named(name), _) => Err(format!(
+                "named placeholder `{{{name}}}` used on tuple variant `{variant}`; \
+                 refer to its fields by position, e.g. `{{0}}`{}",
+                match fields.unnamed.len() {
+                    1 => String::new(),
+                    len => format!(" to `{{{}}}`", len - 1),
+                }
+            )),
+            (Fields::Unnamed(fields), _, Some(index)) if index >= fields.unnamed.len() => {
+                Err(match fields.unnamed.len() {
+                    1 => {
+                        format!("`{variant}` has 1 field, but the message refers to field {index}")
+                    }
+                    len => format!(
+                        "`{variant}` has {len} fields, but the message refers to field {index}"
+                    ),
+                })
+            }
+            (Fields::Unnamed(_), _, _) => Ok(()),
+        }
+    }
+}
+
+/// Finds the candidate closest to `name`, if it is close enough to be a likely typo.
+fn similar<'a>(name: &str, candidates: &'a [String]) -> Option<&'a str> {
+    let threshold = (name.chars().count() / 3).max(1);
+
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+/// The Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous + usize::from(a != *b);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::{Variant, parse_quote};
+
+    use super::edit_distance;
+    use crate::{Interpolate, Message};
+
+    fn validate_message(message: Message, variant: Variant) -> Result<(), String> {
+        Interpolate::parse_message(&message, &variant)
+            .unwrap()
+            .validate()
+            .map_err(|error| error.to_string())
+    }
+
+    fn validate(message: &str, variant: Variant) -> Result<(), String> {
+        Interpolate::parse(message, &variant)
+            .unwrap()
+            .validate()
+            .map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn test_valid_placeholders() {
+        assert_eq!(
+            validate(
+                "{code:>width$}",
+                parse_quote!(V {
+                    code: u8,
+                    width: usize
+                })
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate("{} {0} {1:.*}", parse_quote!(V(u8, usize, f64))),
+            Ok(())
+        );
+        assert_eq!(validate("no fields", parse_quote!(V)), Ok(()));
+    }
+
+    #[test]
+    fn test_unknown_field() {
+        assert_eq!(
+            validate("{cod}", parse_quote!(Failed { code: u8, id: u32 })),
+            Err("`Failed` has no field named `cod`; did you mean `code`?".to_string())
+        );
+        assert_eq!(
+            validate("{x:width$}", parse_quote!(Failed { x: u8 })),
+            Err("`Failed` has no field named `width`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_tuple_index_out_of_range() {
+        assert_eq!(
+            validate("{3}", parse_quote!(Io(String, u8))),
+            Err("`Io` has 2 fields, but the message refers to field 3".to_string())
+        );
+        assert_eq!(
+            validate("{} {}", parse_quote!(Io(String))),
+            Err("`Io` has 1 field, but the message refers to field 1".to_string())
+        );
+        assert_eq!(
+            validate("{:.*}", parse_quote!(Io(usize))),
+            Err("`Io` has 1 field, but the message refers to field 1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_mismatched_variant_kind() {
+        assert_eq!(
+            validate("{path}", parse_quote!(Io(String, u8))),
+            Err(
+                "named placeholder `{path}` used on tuple variant `Io`; refer to its fields by \
+                 position, e.g. `{0}` to `{1}`"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            validate("{0}", parse_quote!(Io { path: String })),
+            Err(
+                "positional placeholder `{0}` used on `Io`, which has named fields; refer to the \
+                 fields by name instead"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            validate("{path}", parse_quote!(Unit)),
+            Err("`Unit` has no fields, but the message refers to `path`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_errors_are_combined() {
+        let variant: Variant = parse_quote!(V { a: u8 });
+        let error = Interpolate::parse("{b} {c}", &variant)
+            .unwrap()
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(error.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn test_extra_arguments() {
+        assert_eq!(
+            validate_message(
+                parse_quote!("{} {0:w$} {n}", .path, w = 4, n = .line),
+                parse_quote!(V {
+                    path: String,
+                    line: u32
+                })
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate_message(parse_quote!("{1}", .0), parse_quote!(V(u8))),
+            Err("there is 1 argument, but the message refers to argument 1".to_string())
+        );
+        assert_eq!(
+            validate_message(parse_quote!("{}", 1, 2), parse_quote!(V)),
+            Err("argument never used".to_string())
+        );
+        assert_eq!(
+            validate_message(
+                parse_quote!("{2} {0.len}", .a, 2, 3),
+                parse_quote!(V { a: String })
+            ),
+            Err("argument never used".to_string())
+        );
+        assert_eq!(
+            validate_message(parse_quote!("", x = 1), parse_quote!(V)),
+            Err("named argument never used".to_string())
+        );
+    }
+
+    #[test]
+    fn test_captured_constants() {
+        assert_eq!(validate("{MAX_RETRIES}", parse_quote!(Unit)), Ok(()));
+        assert_eq!(
+            validate("{} after {MAX_RETRIES:>W$}", parse_quote!(V(u8))),
+            Ok(())
+        );
+        assert_eq!(validate("{MAX} {ID}", parse_quote!(V { ID: u8 })), Ok(()));
+        assert_eq!(
+            validate("{max_retries}", parse_quote!(Unit)),
+            Err("`Unit` has no fields, but the message refers to `max_retries`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_keyword_and_unicode_fields() {
+        assert_eq!(
+            validate(
+                "{type} {match:>größe$}",
+                parse_quote!(V {
+                    r#type: u8,
+                    r#match: u8,
+                    größe: usize
+                })
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate_message(parse_quote!("{}", .type), parse_quote!(V { r#type: u8 })),
+            Ok(())
+        );
+        assert_eq!(
+            validate("{typ}", parse_quote!(V { r#type: u8 })),
+            Err("`V` has no field named `typ`; did you mean `type`?".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unknown_shorthand() {
+        assert_eq!(
+            validate_message(parse_quote!("{}", .pth), parse_quote!(V { path: String })),
+            Err("`V` has no field named `pth`; did you mean `path`?".to_string())
+        );
+        assert_eq!(
+            validate_message(parse_quote!("{}", .2), parse_quote!(V(u8, u8))),
+            Err("`V` has no field `2`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("code", "code"), 0);
+        assert_eq!(edit_distance("cod", "code"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
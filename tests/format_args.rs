@@ -0,0 +1,326 @@
+//! Differential tests of positional arguments against `format_args!`.
+//!
+//! Accepted cases are compiled with `format!`, so rustc itself checks that they are
+//! valid and produces the expected output. Rejected cases carry rustc's diagnostic;
+//! run `cargo test -- --ignored` to re-check both tables against the local rustc.
+
+mod oracle;
+
+use errors::{Argument, Count, FormatString, Segment};
+
+/// Format strings rustc accepts, with their arguments.
+macro_rules! accepted {
+    ($($text:literal $(, $arg:expr)*;)*) => {
+        [$(($text, format!($text $(, $arg)*), &[$(&$arg as &dyn std::fmt::Display),*][..])),*]
+    };
+}
+
+fn accepted() -> [(
+    &'static str,
+    String,
+    &'static [&'static dyn std::fmt::Display],
+); 17] {
+    accepted! {
+        "{}", "a";
+        "{0}", "a";
+        "{01} {0}", "a", "b";
+        "{00} {000}", "a";
+        "{} {1} {0} {}", "a", "b";
+        "{1} {} {0} {}", "a", "b";
+        "{0 } { }", "a";
+        "{:.*}", 2, 1.5;
+        "{:1$}", "a", 3;
+        "{:01}", 7;
+        "{:65535}", "a";
+        "}}";
+        "{{}}";
+        "{{{}}}", "a";
+        "map: }} {{ {0}}}", "a";
+        "{: }", "a";
+        "{0:5\t}", "a";
+    }
+}
+
+/// Indices too large to pass enough arguments to `format!` in a test, with the
+/// index they resolve to.
+const ACCEPTED_INDICES: &[(&str, usize)] = &[("{256}", 256), ("{0256}", 256), ("{1000}", 1000)];
+
+/// Specs whose fill character is a brace or `:`, with the fill.
+const ACCEPTED_FILLS: &[(&str, char)] = &[
+    ("{:}>5}", '}'),
+    ("{:{<5}", '{'),
+    ("{0:}^5}", '}'),
+    ("{::>5}", ':'),
+    ("{:}<}", '}'),
+];
+
+/// Format strings rustc rejects with a diagnostic that differs from ours, e.g.
+/// because it reports a spec's unexpected character as part of the format string.
+const REJECTED_SPECS: &[&str] = &["{a:{b}}", "{a:b:c}", "{:::5}", "{:{}", "{:}}", "{:}5}"];
+
+/// Format strings rustc rejects, with the start of its diagnostic.
+const REJECTED: &[(&str, &str)] = &[
+    ("{+1}", "invalid format string: expected `}`, found `+`"),
+    ("{-1}", "invalid format string: expected `}`, found `-`"),
+    ("{0x1}", "invalid format string: expected `}`, found `x`"),
+    ("{1e2}", "invalid format string: expected `}`, found `e`"),
+    ("{0_1}", "invalid format string: expected `}`, found `_`"),
+    ("{1 2}", "invalid format string: expected `}`, found `2`"),
+    ("{１}", "invalid format string: expected `}`, found `１`"),
+    (
+        "{r#type}",
+        "invalid format string: raw identifiers are not supported",
+    ),
+    (
+        "{65536}",
+        "invalid format string: integer `65536` does not fit into the type `u16` whose range is \
+         `0..=65535`",
+    ),
+    (
+        "{:70000}",
+        "invalid format string: integer `70000` does not fit into the type `u16` whose range is \
+         `0..=65535`",
+    ),
+    (
+        "{:.70000}",
+        "invalid format string: integer `70000` does not fit into the type `u16` whose range is \
+         `0..=65535`",
+    ),
+    (
+        "{:65536$}",
+        "invalid format string: integer `65536` does not fit into the type `u16` whose range is \
+         `0..=65535`",
+    ),
+    ("{:_$}", "invalid format string: invalid argument name `_`"),
+    ("map: }", "invalid format string: unmatched `}` found"),
+    ("{}}", "invalid format string: unmatched `}` found"),
+    ("}}}", "invalid format string: unmatched `}` found"),
+    ("{{}", "invalid format string: unmatched `}` found"),
+    (
+        "a {",
+        "invalid format string: expected `}` but string was terminated",
+    ),
+    (
+        "{{{",
+        "invalid format string: expected `}` but string was terminated",
+    ),
+];
+
+/// Rejected format strings with the note rustc adds to its diagnostic.
+const NOTES: &[(&str, &str)] = &[
+    (
+        "map: }",
+        "if you intended to print `}`, you can escape it using `}}`",
+    ),
+    (
+        "a {",
+        "if you intended to print `{`, you can escape it using `{{`",
+    ),
+    (
+        "{a b}",
+        "if you intended to print `{`, you can escape it using `{{`",
+    ),
+    ("{_}", "argument name cannot be a single underscore"),
+];
+
+/// Characters random format strings are built from, mostly ones with a meaning in
+/// format strings.
+const ALPHABET: &[char] = &[
+    '{', '{', '}', '}', ':', ':', 'a', 'x', '0', '1', '>', '^', '.', '*', '$', '?', ' ', '#', '+',
+    'é',
+];
+
+/// Deterministic pseudo-random format strings of up to 8 characters.
+fn random_strings(count: usize) -> impl Iterator<Item = String> {
+    oracle::random_strings(ALPHABET, 8, 0x2545_f491_4f6c_dd1d, count)
+}
+
+/// Renders `text` the way `format!` would, for placeholders that only select an
+/// argument and a `width$`/`.*` count.
+fn render(text: &str, args: &[&dyn std::fmt::Display]) -> String {
+    let format = FormatString::parse(text).unwrap();
+    let mut next = 0;
+    let mut resolve = |argument: &Argument| match argument {
+        Argument::Implicit => {
+            next += 1;
+            next - 1
+        }
+        Argument::Positional(index) => *index,
+        Argument::Named(name) => panic!("unexpected named argument `{name}`"),
+    };
+    let count = |count: &Option<Count>, resolve: &mut dyn FnMut(&Argument) -> usize| match count {
+        Some(Count::Argument(argument)) => {
+            Some(args[resolve(argument)].to_string().parse().unwrap())
+        }
+        Some(Count::Integer(count)) => Some(*count),
+        None => None,
+    };
+
+    let mut output = String::new();
+    for segment in &format.segments {
+        match segment {
+            Segment::Literal(literal) => {
+                output.push_str(&literal.replace("{{", "{").replace("}}", "}"))
+            }
+            Segment::Placeholder(placeholder) => {
+                let spec = &placeholder.spec;
+                let width = count(&spec.width, &mut resolve).unwrap_or(0);
+                let precision = count(&spec.precision, &mut resolve);
+                let value = args[resolve(&placeholder.argument)];
+
+                let value = match (precision, spec.zero_pad) {
+                    (Some(precision), _) => format!("{value:.precision$}"),
+                    (None, true) => format!("{value:0width$}"),
+                    (None, false) => format!("{value:width$}"),
+                };
+                output.push_str(&value);
+            }
+        }
+    }
+
+    output
+}
+
+#[test]
+fn test_accepted_like_format_args() {
+    for (text, expected, args) in accepted() {
+        assert!(FormatString::parse(text).is_ok(), "{text:?} should parse");
+        assert_eq!(render(text, args), expected, "{text:?}");
+
+        // rustc accepts these with exactly the arguments given
+        let positions = FormatString::parse(text).unwrap().positional_arguments();
+        assert_eq!(positions.required(), args.len(), "{text:?}");
+        assert_eq!(positions.unused(args.len()).count(), 0, "{text:?}");
+    }
+}
+
+#[test]
+fn test_large_indices() {
+    for (text, index) in ACCEPTED_INDICES {
+        let format = FormatString::parse(text).unwrap();
+        let placeholder = format.placeholders().next().unwrap();
+        assert_eq!(
+            placeholder.argument,
+            Argument::Positional(*index),
+            "{text:?}"
+        );
+    }
+}
+
+#[test]
+fn test_rejected_like_format_args() {
+    for (text, message) in REJECTED {
+        let error = FormatString::parse(text).expect_err(text);
+        assert_eq!(error.message(), *message, "{text:?}");
+    }
+
+    for (text, note) in NOTES {
+        let error = FormatString::parse(text).expect_err(text);
+        assert_eq!(error.kind().note(), Some(*note), "{text:?}");
+    }
+
+    for text in REJECTED_SPECS {
+        assert!(
+            FormatString::parse(text).is_err(),
+            "{text:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn test_brace_fills() {
+    for (text, fill) in ACCEPTED_FILLS {
+        let format = FormatString::parse(text).unwrap();
+        let placeholder = format.placeholders().next().unwrap();
+        assert_eq!(placeholder.spec.fill, Some(*fill), "{text:?}");
+        assert_eq!(format.to_string(), *text);
+    }
+}
+
+#[test]
+fn test_random_strings_round_trip() {
+    for text in random_strings(5000) {
+        let Ok(format) = FormatString::parse(&text) else {
+            continue;
+        };
+
+        // The canonical text is accepted and stable
+        let canonical = format.to_string();
+        let reparsed = FormatString::parse(&canonical)
+            .unwrap_or_else(|error| panic!("{text:?} rendered as {canonical:?}: {error}"));
+        assert_eq!(reparsed.to_string(), canonical, "{text:?}");
+        assert_eq!(
+            reparsed.placeholders().count(),
+            format.placeholders().count(),
+            "{text:?}"
+        );
+    }
+}
+
+/// Compiles `format!(text)` with the local rustc, returning its diagnostics on failure.
+fn rustc(text: &str, args: usize) -> Result<(), String> {
+    let args = (0..args).map(|arg| format!(", {arg}")).collect::<String>();
+    let output = oracle::compile(&format!("fn main() {{ let _ = format!({text:?}{args}); }}"));
+
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(String::from_utf8_lossy(&output.stderr).into_owned()),
+    }
+}
+
+#[test]
+#[ignore = "invokes rustc"]
+fn test_tables_match_rustc() {
+    for (text, _, args) in accepted() {
+        if let Err(stderr) = rustc(text, args.len()) {
+            panic!("rustc rejected {text:?}:\n{stderr}");
+        }
+    }
+
+    for (text, index) in ACCEPTED_INDICES {
+        // Every argument must be used, so reference the ones before the index too
+        let used = (0..*index).map(|index| format!("{{{index}}}"));
+        let text = text.to_string() + &used.collect::<String>();
+
+        if let Err(stderr) = rustc(&text, index + 1) {
+            panic!("rustc rejected {text:?}:\n{stderr}");
+        }
+    }
+
+    for (text, message) in REJECTED {
+        let stderr = rustc(text, 0).expect_err(text);
+        assert!(stderr.contains(message), "{text:?}:\n{stderr}");
+    }
+
+    for (text, note) in NOTES {
+        let stderr = rustc(text, 0).expect_err(text);
+        assert!(stderr.contains(note), "{text:?}:\n{stderr}");
+    }
+
+    for (text, _) in ACCEPTED_FILLS {
+        if let Err(stderr) = rustc(text, 1) {
+            panic!("rustc rejected {text:?}:\n{stderr}");
+        }
+    }
+
+    for text in REJECTED_SPECS {
+        rustc(text, 1).expect_err(text);
+    }
+}
+
+#[test]
+#[ignore = "invokes rustc"]
+fn test_random_strings_match_rustc() {
+    let texts = random_strings(2000).collect::<Vec<_>>();
+    let rejected = oracle::rustc_rejections(&texts);
+
+    for (index, text) in texts.iter().enumerate() {
+        if oracle::comparable_with_rustc(text) {
+            assert_eq!(
+                FormatString::parse(text).is_err(),
+                rejected.contains(&index),
+                "{text:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Randomized differential tests of the parser against the reference grammar in
+//! `oracle`.
+//!
+//! `FUZZ_ITERATIONS` and `FUZZ_SEED` change how many strings are checked and which.
+//! For coverage-guided fuzzing, see `fuzz/`.
+
+mod oracle;
+
+/// Characters random format strings are built from, mostly ones with a meaning in
+/// format strings.
+const ALPHABET: &[char] = &[
+    '{', '{', '{', '}', '}', '}', ':', ':', '.', '.', '$', '*', '?', '#', '+', '-', '<', '^', '>',
+    '0', '1', '9', 'a', 'b', 'e', 'x', 'X', 'r', '_', ' ', ' ', '\t', 'é', '·', '１',
+];
+
+fn env(name: &str, default: u64) -> u64 {
+    std::env::var(name).map_or(default, |value| value.parse().unwrap())
+}
+
+#[test]
+fn test_random_strings() {
+    let seed = env("FUZZ_SEED", 0x9e37_79b9_7f4a_7c15);
+    let iterations = env("FUZZ_ITERATIONS", 50_000) as usize;
+
+    for text in oracle::random_strings(ALPHABET, 12, seed, iterations) {
+        oracle::check(&text);
+    }
+}
+
+#[test]
+fn test_regressions() {
+    // Strings the parsers once disagreed on
+    for text in [
+        "{: }",
+        "{:x }",
+        "{:>5\t}",
+        "{:.* }",
+        "{0  :?  }",
+        "{:_$}",
+        "{:.1$x?}",
+        "{:}>5}",
+        "{a:{b}}",
+        "{r#type}",
+        "{a.b.0:>1$}",
+    ] {
+        oracle::check(text);
+    }
+}
+
+#[test]
+#[ignore = "invokes rustc"]
+fn test_oracle_matches_rustc() {
+    let texts = oracle::random_strings(ALPHABET, 12, env("FUZZ_SEED", 1), 3000).collect::<Vec<_>>();
+    let rejected = oracle::rustc_rejections(&texts);
+
+    for (index, text) in texts.iter().enumerate() {
+        if oracle::comparable_with_rustc(text) {
+            let expected = oracle::arguments(text);
+            assert_eq!(
+                expected.is_err(),
+                rejected.contains(&index),
+                "{text:?}: {expected:?}"
+            );
+        }
+    }
+}
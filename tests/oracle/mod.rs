@@ -0,0 +1,358 @@
+//! A reference implementation of the `format_args!` grammar, written independently
+//! of the crate's parser from the grammar in the `std::fmt` docs, and a differential
+//! check of the parser against it.
+//!
+//! Also generates random format strings and compiles them with rustc to check the
+//! grammar itself. Shared by `tests/fuzz.rs`, `tests/format_args.rs` and the
+//! libFuzzer target in `fuzz/`, each of which uses only some of it.
+#![allow(dead_code)]
+
+use std::collections::BTreeSet;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use errors::{Argument, FormatString, Piece, Pieces};
+
+/// An argument a placeholder consumes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arg {
+    /// `{}` or `.*`: the next positional argument.
+    Next,
+
+    /// `{0}` or `0$`.
+    Index(usize),
+
+    /// `{name}` or `name$`.
+    Name(String),
+}
+
+/// Why the reference grammar rejects a format string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    /// `format_args!` rejects it.
+    Syntax,
+
+    /// It accesses a member, e.g. `{a.b}`, which the crate accepts but
+    /// `format_args!` does not.
+    MemberPath,
+}
+
+/// Parses `text` with the `format_args!` grammar, returning the arguments each
+/// placeholder consumes in order: width, precision, then the value.
+pub fn arguments(text: &str) -> Result<Vec<Vec<Arg>>, Rejection> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        index: 0,
+    };
+    let mut placeholders = Vec::new();
+
+    while let Some(c) = parser.next() {
+        match c {
+            '{' if parser.eat('{') => {}
+            '}' if parser.eat('}') => {}
+            '{' => placeholders.push(parser.placeholder()?),
+            '}' => return Err(Rejection::Syntax),
+            _ => {}
+        }
+    }
+
+    Ok(placeholders)
+}
+
+/// Whether rustc's verdict on `text` can be compared with the crate's parser, which
+/// is not the case for member paths such as `{a.b}` that only the crate accepts.
+pub fn comparable_with_rustc(text: &str) -> bool {
+    !matches!(arguments(text), Err(Rejection::MemberPath))
+}
+
+/// Checks the crate's parser against [`arguments`], panicking on a difference.
+pub fn check(text: &str) {
+    let expected = arguments(text);
+    let format = FormatString::parse(text);
+
+    // Both parsers agree with each other
+    let pieces = Pieces::new(text).try_for_each(|piece| match piece? {
+        Piece::Placeholder(placeholder) => placeholder.parse_spec().map(drop),
+        Piece::Literal { .. } => Ok(()),
+    });
+    assert_eq!(pieces.is_ok(), format.is_ok(), "{text:?}: Pieces disagrees");
+
+    let format = match (expected, format) {
+        (Err(Rejection::MemberPath), _) => return,
+        (Err(Rejection::Syntax), Err(_)) => return,
+        (Err(Rejection::Syntax), Ok(format)) => {
+            panic!("{text:?} should be rejected, parsed as {format:?}")
+        }
+        (Ok(_), Err(error)) => panic!("{text:?} should be accepted: {error}"),
+        (Ok(expected), Ok(format)) => {
+            let actual = format
+                .placeholders()
+                .map(|placeholder| placeholder.arguments().map(arg).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            assert_eq!(actual, expected, "{text:?}");
+            format
+        }
+    };
+
+    // The canonical text means the same
+    let canonical = format.to_string();
+    assert_eq!(
+        arguments(&canonical),
+        arguments(text),
+        "{text:?} rendered as {canonical:?}"
+    );
+}
+
+fn arg(argument: &Argument) -> Arg {
+    match argument {
+        Argument::Implicit => Arg::Next,
+        Argument::Positional(index) => Arg::Index(*index),
+        Argument::Named(name) => Arg::Name(name.clone()),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        self.chars.get(self.index + 1).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.index += 1;
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let eaten = self.peek() == Some(c);
+        self.index += usize::from(eaten);
+        eaten
+    }
+
+    fn whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.index += 1;
+        }
+    }
+
+    /// `'{' [argument] [':' format_spec] [ws] * '}'`, after the `{`.
+    fn placeholder(&mut self) -> Result<Vec<Arg>, Rejection> {
+        let value = self.argument()?.unwrap_or(Arg::Next);
+        if self.peek() == Some('.') {
+            return Err(Rejection::MemberPath);
+        }
+        self.whitespace();
+
+        let mut arguments = Vec::new();
+        if self.eat(':') {
+            self.spec(&mut arguments)?;
+        }
+
+        match self.next() {
+            Some('}') => {
+                arguments.push(value);
+                Ok(arguments)
+            }
+            _ => Err(Rejection::Syntax),
+        }
+    }
+
+    /// `integer | identifier`, or nothing.
+    fn argument(&mut self) -> Result<Option<Arg>, Rejection> {
+        if let Some(index) = self.integer()? {
+            return Ok(Some(Arg::Index(index)));
+        }
+
+        match self.identifier().as_deref() {
+            None => Ok(None),
+            Some("_") => Err(Rejection::Syntax),
+            Some("r") if self.peek() == Some('#') => Err(Rejection::Syntax),
+            Some(name) => Ok(Some(Arg::Name(name.to_string()))),
+        }
+    }
+
+    /// Digits, which must fit into a `u16`.
+    fn integer(&mut self) -> Result<Option<usize>, Rejection> {
+        let mut digits = String::new();
+        while let Some(digit) = self.peek().filter(char::is_ascii_digit) {
+            digits.push(digit);
+            self.index += 1;
+        }
+
+        match digits.is_empty() {
+            true => Ok(None),
+            false => match digits.parse::<u16>() {
+                Ok(integer) => Ok(Some(usize::from(integer))),
+                Err(_) => Err(Rejection::Syntax),
+            },
+        }
+    }
+
+    /// `XID_Start XID_Continue*` or `_ XID_Continue*`.
+    fn identifier(&mut self) -> Option<String> {
+        let first = self
+            .peek()
+            .filter(|&c| c == '_' || unicode_ident::is_xid_start(c))?;
+        let mut identifier = String::from(first);
+        self.index += 1;
+
+        while let Some(c) = self.peek().filter(|&c| unicode_ident::is_xid_continue(c)) {
+            identifier.push(c);
+            self.index += 1;
+        }
+        Some(identifier)
+    }
+
+    /// `[[fill] align] [sign] ['#'] ['0'] [width] ['.' precision] type`
+    fn spec(&mut self, arguments: &mut Vec<Arg>) -> Result<(), Rejection> {
+        let is_align = |c: Option<char>| matches!(c, Some('<' | '^' | '>'));
+        if is_align(self.peek_second()) {
+            self.index += 2;
+        } else if is_align(self.peek()) {
+            self.index += 1;
+        }
+
+        if !self.eat('+') {
+            self.eat('-');
+        }
+        self.eat('#');
+        if self.peek() == Some('0') && self.peek_second() != Some('$') {
+            self.index += 1;
+        }
+
+        arguments.extend(self.count()?);
+        if self.eat('.') {
+            match self.eat('*') {
+                true => arguments.push(Arg::Next),
+                false => arguments.extend(self.count()?),
+            }
+        }
+
+        if self.peek() == Some('?') {
+            self.index += 1;
+        } else if matches!(self.peek(), Some('x' | 'X')) && self.peek_second() == Some('?') {
+            self.index += 2;
+        } else if let Some(ty) = self.identifier()
+            && !["o", "x", "X", "p", "b", "e", "E"].contains(&ty.as_str())
+        {
+            return Err(Rejection::Syntax);
+        }
+
+        self.whitespace();
+        Ok(())
+    }
+
+    /// `integer | integer '$' | identifier '$'`, leaving a bare identifier for the type.
+    fn count(&mut self) -> Result<Option<Arg>, Rejection> {
+        let start = self.index;
+        if let Some(integer) = self.integer()? {
+            return Ok(self.eat('$').then_some(Arg::Index(integer)));
+        }
+
+        match self.identifier() {
+            Some(name) if self.eat('$') => match name.as_str() {
+                "_" => Err(Rejection::Syntax),
+                _ => Ok(Some(Arg::Name(name))),
+            },
+            _ => {
+                self.index = start;
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Deterministic pseudo-random strings of up to `max_length` characters from
+/// `alphabet`, so that failures reproduce.
+pub fn random_strings(
+    alphabet: &'static [char],
+    max_length: usize,
+    seed: u64,
+    count: usize,
+) -> impl Iterator<Item = String> {
+    let mut state = seed.max(1);
+    let mut next = move || {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as usize
+    };
+
+    (0..count).map(move |_| {
+        let length = next() % (max_length + 1);
+        (0..length)
+            .map(|_| alphabet[next() % alphabet.len()])
+            .collect()
+    })
+}
+
+/// Compiles `source` as a crate with the local rustc, or `$RUSTC`, without linking.
+pub fn compile(source: &str) -> Output {
+    // Tests run in parallel, so each compilation gets its own directory
+    static COMPILATIONS: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "errors-oracle-{}-{}",
+        std::process::id(),
+        COMPILATIONS.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("main.rs"), source).unwrap();
+
+    let output = Command::new(std::env::var("RUSTC").unwrap_or("rustc".into()))
+        .args(["--edition", "2024", "--emit", "metadata", "--out-dir"])
+        .arg(&dir)
+        .arg(dir.join("main.rs"))
+        .output();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    output.expect("failed to run rustc")
+}
+
+/// Compiles one `format!` per text, returning the indices of the texts rustc rejects
+/// with a syntax error, as opposed to errors about arguments or types.
+///
+/// No arguments are passed, as the diagnostic about unused ones can crash rustc.
+/// Instead, the single-letter names random strings use are declared as variables.
+pub fn rustc_rejections(texts: &[String]) -> BTreeSet<usize> {
+    // One `format!` per line, so that diagnostics can be matched to the strings
+    let source = texts
+        .iter()
+        .map(|text| format!("let _ = format!({text:?});\n"))
+        .collect::<String>();
+    let source =
+        format!("fn main() {{ let (a, b, e, x, r, é) = (0usize, 0, 0, 0, 0, 0);\n{source}}}");
+
+    let output = compile(&source);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        !stderr.contains("the compiler unexpectedly panicked"),
+        "{stderr}"
+    );
+
+    let mut rejected = BTreeSet::new();
+    let mut syntax_error = false;
+    for line in stderr.lines() {
+        if line.starts_with("error") {
+            syntax_error = ["invalid format string", "unknown format trait"]
+                .iter()
+                .any(|message| line.contains(message));
+        } else if let Some(location) = line.trim_start().strip_prefix("--> ")
+            && syntax_error
+        {
+            let line = location.split(':').nth(1).unwrap();
+            rejected.insert(line.parse::<usize>().unwrap() - 2);
+            syntax_error = false;
+        }
+    }
+
+    rejected
+}
@@ -0,0 +1,96 @@
+//! Differential tests of runtime rendering against `format!`.
+
+use std::collections::HashMap;
+
+use errors::{FormatString, Value};
+
+/// Asserts that rendering each format string with its single positional argument
+/// gives the same output as `format!`.
+macro_rules! same_as_format {
+    ($($text:literal, $value:expr;)*) => {$(
+        let map = HashMap::from([("0", Value::from($value))]);
+        let rendered = FormatString::parse($text).unwrap().render_map(&map);
+        assert_eq!(rendered.as_deref(), Ok(format!($text, $value).as_str()), "{:?}", $text);
+    )*};
+}
+
+#[test]
+fn test_strings() {
+    same_as_format! {
+        "{}", "text";
+        "[{:8}]", "text";
+        "[{:>8}]", "text";
+        "[{:*^9}]", "text";
+        "[{:.2}]", "text";
+        "[{:>6.2}]", "text";
+        "[{:08}]", "text";
+        "[{:?}]", "quote\"d";
+        "[{:>10?}]", "ab";
+        "[{:-<5}]", 'c';
+        "[{:?}]", 'c';
+        "[{:>5?}]", 'c';
+        "[{:7}]", true;
+        "[{:.1}]", false;
+    }
+}
+
+#[test]
+fn test_integers() {
+    same_as_format! {
+        "{}", 42;
+        "[{:5}]", 42;
+        "[{:<5}]", 42;
+        "[{:^+7}]", 42;
+        "[{:05}]", -42;
+        "[{:+05}]", 42;
+        "[{:#x}]", 255;
+        "[{:#010x}]", 255;
+        "[{:X}]", 255u64;
+        "[{:o}]", 8;
+        "[{:#b}]", 5;
+        "[{:x}]", -1i64;
+        "[{:x}]", -3i32;
+        "[{:#X?}]", -3i32;
+        "[{:+#010b}]", -3i8;
+        "[{:o}]", i16::MIN;
+        "[{:x}]", -1isize;
+        "[{:e}]", 1500;
+        "[{:.1E}]", 1500;
+        "[{:#?}]", 7;
+        "[{:x?}]", 255;
+        "[{:~>8}]", u64::MAX;
+    }
+}
+
+#[test]
+fn test_floats() {
+    same_as_format! {
+        "{}", 1.5;
+        "[{:.3}]", 1.0 / 3.0;
+        "[{:8.2}]", -2.25;
+        "[{:<8.2}]", 2.25;
+        "[{:+.1}]", 2.25;
+        "[{:08.2}]", -2.25;
+        "[{:e}]", 1234.5;
+        "[{:.2E}]", 1234.5;
+        "[{:?}]", 1.0;
+        "[{:+?}]", 0.1;
+    }
+}
+
+#[test]
+fn test_counts() {
+    let map = HashMap::from([
+        ("0", Value::from(2)),
+        ("1", Value::from(2.71)),
+        ("width", Value::from(8usize)),
+    ]);
+    let rendered = FormatString::parse("[{1:>width$.0$}] [{:.*}]")
+        .unwrap()
+        .render_map(&map);
+
+    assert_eq!(
+        rendered,
+        Ok(format!("[{1:>width$.0$}] [{:.*}]", 2, 2.71, width = 8))
+    );
+}